@@ -0,0 +1,146 @@
+use super::{flow_map_system::FlowMaps, Map, TileType};
+use rltk::{Point, RandomNumberGenerator};
+use std::time::{Duration, Instant};
+
+const BENCH_WIDTH: i32 = 240;
+const BENCH_HEIGHT: i32 = 160;
+const BENCH_MONSTERS: usize = 150;
+const BENCH_TURNS: usize = 25;
+
+/// Times every monster searching for its own path to the player against all of them
+/// sharing one flow map, on a map much bigger and busier than a normal level. Once the
+/// monsters start crowding round the player most of their searches fail, and a failed
+/// search has to cover the whole map, so the first half takes a while.
+///
+/// Run with `cargo run --release -- --benchmark`.
+pub fn run_pathing_benchmark() {
+    let mut rng = RandomNumberGenerator::seeded(2022);
+    let mut map = pillared_map(&mut rng);
+    let player = Point::new(BENCH_WIDTH / 2, BENCH_HEIGHT / 2);
+    let monsters = place_monsters(&mut map, player, &mut rng);
+
+    println!(
+        "Pathing {} monsters to the player on a {}x{} map, for {} turns",
+        monsters.len(),
+        BENCH_WIDTH,
+        BENCH_HEIGHT,
+        BENCH_TURNS
+    );
+
+    let (a_star_time, a_star_arrived) =
+        time_turns(map.clone(), monsters.clone(), player, a_star_turn);
+    report("a_star_search per monster", a_star_time, a_star_arrived);
+
+    let (flow_time, flow_arrived) = time_turns(map, monsters, player, flow_map_turn);
+    report("shared flow maps", flow_time, flow_arrived);
+
+    println!(
+        "Flow maps were {:.1}x faster",
+        a_star_time.as_secs_f64() / flow_time.as_secs_f64()
+    );
+}
+
+fn report(method: &str, time: Duration, arrived: usize) {
+    println!(
+        "  {:<28} {:>9.2} ms total, {:>7.2} ms per turn, {} monsters reached the player",
+        method,
+        time.as_secs_f64() * 1000.0,
+        time.as_secs_f64() * 1000.0 / BENCH_TURNS as f64,
+        arrived
+    );
+}
+
+/// Plays out the benchmark turns with one way of moving monsters, and returns how long
+/// they took and how many monsters ended up next to the player.
+fn time_turns(
+    mut map: Map,
+    mut monsters: Vec<usize>,
+    player: Point,
+    turn: fn(&mut Map, &mut [usize], Point),
+) -> (Duration, usize) {
+    let start = Instant::now();
+    for _ in 0..BENCH_TURNS {
+        turn(&mut map, &mut monsters, player);
+    }
+    let elapsed = start.elapsed();
+
+    let arrived = monsters
+        .iter()
+        .filter(|idx| {
+            let (x, y) = (**idx as i32 % map.width, **idx as i32 / map.width);
+            i32::max(i32::abs(x - player.x), i32::abs(y - player.y)) <= 1
+        })
+        .count();
+    (elapsed, arrived)
+}
+
+/// The old way: every monster runs its own search.
+fn a_star_turn(map: &mut Map, monsters: &mut [usize], player: Point) {
+    let player_idx = map.xy_idx(player.x, player.y);
+    for monster in monsters.iter_mut() {
+        let path = rltk::a_star_search(*monster, player_idx, &*map);
+        if path.success && path.steps.len() > 2 {
+            step(map, monster, path.steps[1]);
+        }
+    }
+}
+
+/// The new way: one set of flow maps, and every monster rolls downhill.
+fn flow_map_turn(map: &mut Map, monsters: &mut [usize], player: Point) {
+    let flow_maps = FlowMaps::build(map, player, false);
+    let player_idx = map.xy_idx(player.x, player.y);
+    for monster in monsters.iter_mut() {
+        if let Some(next) = flow_maps.to_player.downhill(map, *monster, &[]) {
+            if next != player_idx {
+                step(map, monster, next);
+            }
+        }
+    }
+}
+
+fn step(map: &mut Map, monster: &mut usize, next: usize) {
+    map.blocked[*monster] = false;
+    map.blocked[next] = true;
+    *monster = next;
+}
+
+/// An open map, walled in, with stone pillars scattered across it.
+fn pillared_map(rng: &mut RandomNumberGenerator) -> Map {
+    let count = (BENCH_WIDTH * BENCH_HEIGHT) as usize;
+    let mut map = Map {
+        tiles: vec![TileType::Floor; count],
+        width: BENCH_WIDTH,
+        height: BENCH_HEIGHT,
+        revealed_tiles: vec![false; count],
+        visible_tiles: vec![false; count],
+        blocked: vec![false; count],
+        depth: 1,
+        tile_content: vec![Vec::new(); count],
+    };
+    for y in 0..BENCH_HEIGHT {
+        for x in 0..BENCH_WIDTH {
+            let edge = x == 0 || y == 0 || x == BENCH_WIDTH - 1 || y == BENCH_HEIGHT - 1;
+            if edge || rng.roll_dice(1, 100) <= 15 {
+                let idx = map.xy_idx(x, y);
+                map.tiles[idx] = TileType::StoneWall;
+            }
+        }
+    }
+    let player_idx = map.xy_idx(BENCH_WIDTH / 2, BENCH_HEIGHT / 2);
+    map.tiles[player_idx] = TileType::Floor;
+    map.populate_blocked();
+    map
+}
+
+fn place_monsters(map: &mut Map, player: Point, rng: &mut RandomNumberGenerator) -> Vec<usize> {
+    let mut monsters: Vec<usize> = Vec::new();
+    while monsters.len() < BENCH_MONSTERS {
+        let (x, y) = (rng.range(1, BENCH_WIDTH - 1), rng.range(1, BENCH_HEIGHT - 1));
+        let idx = map.xy_idx(x, y);
+        if !map.blocked[idx] && !monsters.contains(&idx) && Point::new(x, y) != player {
+            monsters.push(idx);
+            map.blocked[idx] = true;
+        }
+    }
+    monsters
+}
@@ -0,0 +1,271 @@
+use super::{
+    flow_map_system::FlowMaps, gamelog::GameLog, spawner, status_effect_system::is_affected,
+    telegraph_system::WIND_UP_TURNS, Boss, Brain, CombatStats, DamageType, EntityMoved, Map,
+    Monster, Name, Position, RunState, StatusEffects, StatusKind, Telegraph, Viewshed,
+    WantsToMelee,
+};
+use rltk::{Point, RandomNumberGenerator};
+use specs::prelude::*;
+
+/// The most raised dead the boss keeps around at once.
+const MAX_MINIONS: usize = 4;
+
+/// How many of the dead the boss raises at a time.
+const MINIONS_PER_SUMMON: usize = 2;
+
+/// Turns between one lot of raised dead and the next.
+const SUMMON_COOLDOWN: i32 = 8;
+
+/// How far the boss's bone spears fly.
+const SPEAR_LENGTH: i32 = 12;
+
+/// What the boss does each turn. It fights in three phases as it is worn down: at first
+/// it slams the ground around it and throws bone spears; wounded, it starts raising the
+/// dead and marking the ground under the player; near the end it attacks almost every
+/// turn, and further. Every area attack is highlighted a turn before it lands.
+pub struct BossSystem {}
+
+impl<'a> System<'a> for BossSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        WriteExpect<'a, Map>,
+        ReadExpect<'a, Point>,
+        ReadExpect<'a, Entity>,
+        ReadExpect<'a, RunState>,
+        ReadExpect<'a, FlowMaps>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        WriteExpect<'a, GameLog>,
+        Read<'a, LazyUpdate>,
+        WriteStorage<'a, Boss>,
+        WriteStorage<'a, Telegraph>,
+        WriteStorage<'a, Position>,
+        WriteStorage<'a, Viewshed>,
+        WriteStorage<'a, EntityMoved>,
+        WriteStorage<'a, WantsToMelee>,
+        ReadStorage<'a, CombatStats>,
+        ReadStorage<'a, StatusEffects>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, Monster>,
+        ReadStorage<'a, Brain>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            mut map,
+            player_pos,
+            player_entity,
+            runstate,
+            flow_maps,
+            mut rng,
+            mut log,
+            lazy,
+            mut bosses,
+            mut telegraphs,
+            mut positions,
+            mut viewsheds,
+            mut entity_moved,
+            mut wants_to_melee,
+            combat_stats,
+            statuses,
+            names,
+            monsters,
+            brains,
+        ) = data;
+
+        if *runstate != RunState::MonsterTurn {
+            return;
+        }
+
+        let minions = (&monsters, &brains).join().count();
+
+        for (entity, boss, pos, viewshed) in
+            (&entities, &mut bosses, &mut positions, &mut viewsheds).join()
+        {
+            let name = names
+                .get(entity)
+                .map_or("The boss".to_string(), |n| n.name.clone());
+
+            // Wear it down and it gets more dangerous
+            let phase = combat_stats.get(entity).map_or(1, |stats| {
+                if stats.hp * 3 > stats.max_hp * 2 {
+                    1
+                } else if stats.hp * 3 > stats.max_hp {
+                    2
+                } else {
+                    3
+                }
+            });
+            if phase > boss.phase {
+                boss.phase = phase;
+                boss.attack_cooldown = 0;
+                boss.summon_cooldown = 0;
+                log.entries.push(match phase {
+                    2 => format!("{} laughs: \"You cannot kill the deathless!\"", name),
+                    _ => format!("{} blazes with cold fire!", name),
+                });
+            }
+            boss.attack_cooldown -= 1;
+            boss.summon_cooldown -= 1;
+
+            // Stand still while an attack is winding up, or if stunned
+            if telegraphs.get(entity).is_some() || is_affected(&statuses, entity, StatusKind::Stun)
+            {
+                continue;
+            }
+
+            let here = Point::new(pos.x, pos.y);
+            let sees_player = viewshed.visible_tiles.contains(&*player_pos);
+            let distance = rltk::DistanceAlg::Pythagoras.distance2d(here, *player_pos);
+
+            if sees_player && boss.phase >= 2 && boss.summon_cooldown <= 0 && minions < MAX_MINIONS
+            {
+                boss.summon_cooldown = SUMMON_COOLDOWN;
+                log.entries.push(format!("{} raises the dead!", name));
+                for (x, y) in free_tiles_around(&map, here, MINIONS_PER_SUMMON) {
+                    let idx = map.xy_idx(x, y);
+                    map.blocked[idx] = true;
+                    lazy.exec_mut(move |ecs| spawner::raised_skeleton(ecs, x, y));
+                }
+            } else if sees_player && boss.attack_cooldown <= 0 {
+                let telegraph = choose_attack(&map, here, *player_pos, boss.phase, &mut rng);
+                log.entries
+                    .push(format!("{} prepares a {}!", name, telegraph.name));
+                boss.attack_cooldown = WIND_UP_TURNS + 4 - boss.phase;
+                telegraphs
+                    .insert(entity, telegraph)
+                    .expect("Unable to insert telegraph");
+            } else if distance < 1.5 {
+                wants_to_melee
+                    .insert(entity, WantsToMelee { target: *player_entity })
+                    .expect("Unable to insert attack");
+            } else {
+                let idx = map.xy_idx(pos.x, pos.y);
+                if let Some(next) = flow_maps.to_player.downhill(&map, idx, &[]) {
+                    map.blocked[idx] = false;
+                    map.blocked[next] = true;
+                    pos.x = next as i32 % map.width;
+                    pos.y = next as i32 / map.width;
+                    viewshed.dirty = true;
+                    entity_moved
+                        .insert(entity, EntityMoved {})
+                        .expect("Unable to insert marker");
+                }
+            }
+        }
+    }
+}
+
+/// Picks the boss's next area attack: a slam when the player is close, and otherwise
+/// spears or marks on the ground, with more of them in later phases.
+fn choose_attack(
+    map: &Map,
+    boss: Point,
+    player: Point,
+    phase: i32,
+    rng: &mut RandomNumberGenerator,
+) -> Telegraph {
+    let distance = rltk::DistanceAlg::Pythagoras.distance2d(boss, player);
+    let (name, tiles, damage, damage_type) = if distance < 3.0 {
+        ("grave slam", slam_tiles(map, boss, 1 + phase), 10, DamageType::Blunt)
+    } else if phase == 1 || rng.roll_dice(1, 2) == 1 {
+        (
+            "volley of bone spears",
+            spear_tiles(map, boss, phase >= 3),
+            8,
+            DamageType::Piercing,
+        )
+    } else {
+        let mut centres = vec![player];
+        for _ in 1..phase {
+            centres.push(Point::new(player.x + rng.range(-3, 4), player.y + rng.range(-3, 4)));
+        }
+        ("deathly mark", mark_tiles(map, &centres), 7, DamageType::Cold)
+    };
+    Telegraph {
+        name: name.to_string(),
+        tiles,
+        damage,
+        damage_type,
+        turns: WIND_UP_TURNS,
+    }
+}
+
+/// Whether an area attack could reach a tile.
+fn in_arena(map: &Map, point: Point) -> bool {
+    point.x > 0
+        && point.x < map.width - 1
+        && point.y > 0
+        && point.y < map.height - 1
+        && map.is_walkable(map.xy_idx(point.x, point.y))
+}
+
+/// Every open tile within `radius` of the boss.
+fn slam_tiles(map: &Map, centre: Point, radius: i32) -> Vec<Point> {
+    let mut tiles: Vec<Point> = Vec::new();
+    for y in centre.y - radius..=centre.y + radius {
+        for x in centre.x - radius..=centre.x + radius {
+            let tile = Point::new(x, y);
+            if tile != centre
+                && rltk::DistanceAlg::Pythagoras.distance2d(centre, tile) <= radius as f32
+                && in_arena(map, tile)
+            {
+                tiles.push(tile);
+            }
+        }
+    }
+    tiles
+}
+
+/// Straight lines out from the boss, until each hits a wall: along the four main
+/// directions, and the diagonals too if `diagonals` is set.
+fn spear_tiles(map: &Map, from: Point, diagonals: bool) -> Vec<Point> {
+    let mut directions = vec![(0, -1), (0, 1), (-1, 0), (1, 0)];
+    if diagonals {
+        directions.extend_from_slice(&[(-1, -1), (1, -1), (-1, 1), (1, 1)]);
+    }
+    let mut tiles: Vec<Point> = Vec::new();
+    for (dx, dy) in directions {
+        for step in 1..=SPEAR_LENGTH {
+            let tile = Point::new(from.x + dx * step, from.y + dy * step);
+            if !in_arena(map, tile) {
+                break;
+            }
+            tiles.push(tile);
+        }
+    }
+    tiles
+}
+
+/// A three by three patch round each of the centres.
+fn mark_tiles(map: &Map, centres: &[Point]) -> Vec<Point> {
+    let mut tiles: Vec<Point> = Vec::new();
+    for centre in centres.iter() {
+        for y in centre.y - 1..=centre.y + 1 {
+            for x in centre.x - 1..=centre.x + 1 {
+                let tile = Point::new(x, y);
+                if in_arena(map, tile) && !tiles.contains(&tile) {
+                    tiles.push(tile);
+                }
+            }
+        }
+    }
+    tiles
+}
+
+/// Up to `count` empty tiles next to a point.
+fn free_tiles_around(map: &Map, centre: Point, count: usize) -> Vec<(i32, i32)> {
+    let mut free: Vec<(i32, i32)> = Vec::new();
+    for y in centre.y - 1..=centre.y + 1 {
+        for x in centre.x - 1..=centre.x + 1 {
+            if free.len() < count
+                && in_arena(map, Point::new(x, y))
+                && !map.blocked[map.xy_idx(x, y)]
+            {
+                free.push((x, y));
+            }
+        }
+    }
+    free
+}
@@ -1,4 +1,4 @@
-use rltk::RGB;
+use rltk::{RandomNumberGenerator, RGB};
 use serde::{Deserialize, Serialize};
 use specs::error::NoError;
 use specs::prelude::*;
@@ -57,10 +57,47 @@ pub struct CombatStats {
     pub power: i32,
 }
 
+impl CombatStats {
+    /// Full health, and the two points of stamina a monster gets.
+    pub fn new(hp: i32, power: i32, defense: i32) -> CombatStats {
+        CombatStats { max_hp: hp, hp, max_stamina: 2, stamina: 2, defense, power }
+    }
+}
+
+/// Progress towards regaining the next point of stamina
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Stamina {
+    pub recovery: i32,
+}
+
+/// Ran out of stamina, and can't take costly actions until half of it is back
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Exhausted {}
+
+/// An evasion window: attacks during the turn after a dodge miss, unless they come
+/// from the direction the dodge went in
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Dodging {
+    pub from_x: i32,
+    pub from_y: i32,
+    pub dx: i32,
+    pub dy: i32,
+}
+
 #[derive(Component, Debug, ConvertSaveload, Clone)]
 pub struct WeaponStats {
     pub power: i32,
+    pub reach: i32,
+    pub damage_type: DamageType,
     pub owner: Entity,
+    pub item: Entity,
+}
+
+impl WeaponStats {
+    /// How far the weapon can be from its owner before it is out of their grasp
+    pub fn tether(&self) -> i32 {
+        self.reach + 1
+    }
 }
 
 #[derive(Component, Debug, ConvertSaveload, Clone)]
@@ -68,17 +105,75 @@ pub struct WantsToMelee {
     pub target: Entity,
 }
 
+/// Marks a melee attack as a stamina-fuelled heavy blow
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct PowerAttack {}
+
+/// A shove from a heavy blow, resolved a tile at a time by the knockback system
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Knockback {
+    pub dx: i32,
+    pub dy: i32,
+    pub distance: i32,
+}
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Serialize, Deserialize)]
+pub enum DamageType {
+    Slashing,
+    Piercing,
+    Blunt,
+    Fire,
+    Cold,
+    Poison,
+}
+
+impl DamageType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            DamageType::Slashing => "slashing",
+            DamageType::Piercing => "piercing",
+            DamageType::Blunt => "blunt",
+            DamageType::Fire => "fire",
+            DamageType::Cold => "cold",
+            DamageType::Poison => "poison",
+        }
+    }
+}
+
+/// Takes half damage from these types. Armour with this protects whoever has it equipped.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Resistant {
+    pub damage_types: Vec<DamageType>,
+}
+
+/// Takes double damage from these types
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Vulnerable {
+    pub damage_types: Vec<DamageType>,
+}
+
+/// The kind of damage a creature deals when it attacks without a weapon
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct NaturalAttack {
+    pub damage_type: DamageType,
+}
+
 #[derive(Component, Debug, ConvertSaveload, Clone)]
 pub struct SufferDamage {
-    pub amount: Vec<i32>,
+    pub amount: Vec<(i32, DamageType)>,
 }
 
 impl SufferDamage {
-    pub fn new_damage(store: &mut WriteStorage<SufferDamage>, victim: Entity, amount: i32) {
+    pub fn new_damage(
+        store: &mut WriteStorage<SufferDamage>,
+        victim: Entity,
+        amount: i32,
+        damage_type: DamageType,
+    ) {
         if let Some(suffering) = store.get_mut(victim) {
-            suffering.amount.push(amount);
+            suffering.amount.push((amount, damage_type));
         } else {
-            let dmg = SufferDamage { amount: vec![amount] };
+            let dmg = SufferDamage { amount: vec![(amount, damage_type)] };
             store.insert(victim, dmg).expect("Unable to insert damage");
         }
     }
@@ -95,9 +190,45 @@ pub struct Ranged {
     pub range: i32,
 }
 
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Serialize, Deserialize)]
+pub enum AmmoKind {
+    Arrow,
+    Bolt,
+    Thrown,
+}
+
+impl AmmoKind {
+    /// What a bundle of this ammunition is called, count included
+    pub fn bundle_name(&self, count: i32) -> String {
+        let name = match self {
+            AmmoKind::Arrow => "Arrows",
+            AmmoKind::Bolt => "Bolts",
+            AmmoKind::Thrown => "Throwing Knives",
+        };
+        format!("{} ({})", name, count)
+    }
+}
+
+/// A bundle of projectiles. Thrown weapons are their own ammunition.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Ammo {
+    pub kind: AmmoKind,
+    pub count: i32,
+}
+
+/// Shoots ammunition of a particular kind along a line of fire
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct RangedWeapon {
+    pub range: i32,
+    pub power: i32,
+    pub damage_type: DamageType,
+    pub ammo: AmmoKind,
+}
+
 #[derive(Component, Debug, ConvertSaveload, Clone)]
 pub struct InflictsDamage {
     pub damage: i32,
+    pub damage_type: DamageType,
 }
 
 #[derive(Component, Debug, ConvertSaveload, Clone)]
@@ -105,9 +236,109 @@ pub struct AreaOfEffect {
     pub radius: i32,
 }
 
-#[derive(Component, Debug, ConvertSaveload, Clone)]
-pub struct Confusion {
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Serialize, Deserialize)]
+pub enum StatusKind {
+    Poison,
+    Bleeding,
+    Stun,
+    Slow,
+    Haste,
+    Regeneration,
+    Confusion,
+    Immobilised,
+}
+
+impl StatusKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            StatusKind::Poison => "Poisoned",
+            StatusKind::Bleeding => "Bleeding",
+            StatusKind::Stun => "Stunned",
+            StatusKind::Slow => "Slowed",
+            StatusKind::Haste => "Hasted",
+            StatusKind::Regeneration => "Regenerating",
+            StatusKind::Confusion => "Confused",
+            StatusKind::Immobilised => "Immobilised",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StatusEffect {
+    pub kind: StatusKind,
     pub turns: i32,
+    pub potency: i32,
+}
+
+/// Every status effect currently affecting an entity, ticked down by the status effect system
+#[derive(Component, Debug, Serialize, Deserialize, Clone, Default)]
+pub struct StatusEffects {
+    pub effects: Vec<StatusEffect>,
+}
+
+impl StatusEffects {
+    pub fn get(&self, kind: StatusKind) -> Option<&StatusEffect> {
+        self.effects.iter().find(|e| e.kind == kind)
+    }
+
+    pub fn has(&self, kind: StatusKind) -> bool {
+        self.get(kind).is_some()
+    }
+
+    pub fn remove_effect(&mut self, kind: StatusKind) {
+        self.effects.retain(|e| e.kind != kind);
+    }
+
+    /// Adds an effect, following the stacking rules: damage over time gets stronger,
+    /// regeneration lasts longer, and everything else just has its duration refreshed.
+    /// Haste and slow cancel each other out.
+    pub fn add_effect(
+        store: &mut WriteStorage<StatusEffects>,
+        victim: Entity,
+        kind: StatusKind,
+        turns: i32,
+        potency: i32,
+    ) {
+        if store.get(victim).is_none() {
+            store
+                .insert(victim, StatusEffects::default())
+                .expect("Unable to insert status");
+        }
+        let statuses = store.get_mut(victim).unwrap();
+
+        let opposite = match kind {
+            StatusKind::Haste => Some(StatusKind::Slow),
+            StatusKind::Slow => Some(StatusKind::Haste),
+            _ => None,
+        };
+        if let Some(opposite) = opposite {
+            if statuses.has(opposite) {
+                statuses.effects.retain(|e| e.kind != opposite);
+                return;
+            }
+        }
+
+        match statuses.effects.iter_mut().find(|e| e.kind == kind) {
+            None => statuses.effects.push(StatusEffect { kind, turns, potency }),
+            Some(existing) => match kind {
+                StatusKind::Poison | StatusKind::Bleeding => {
+                    existing.potency += potency;
+                    existing.turns = i32::max(existing.turns, turns);
+                }
+                StatusKind::Regeneration => existing.turns += turns,
+                _ => existing.turns = i32::max(existing.turns, turns),
+            },
+        }
+    }
+}
+
+/// Gives whatever this item, trap or creature hits a status effect, `chance` percent of the time
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct InflictsStatus {
+    pub kind: StatusKind,
+    pub turns: i32,
+    pub potency: i32,
+    pub chance: i32,
 }
 
 #[derive(Component, Debug, ConvertSaveload, Clone)]
@@ -115,6 +346,11 @@ pub struct ProvidesHealing {
     pub heal_amount: i32,
 }
 
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct ProvidesRepair {
+    pub repair_amount: i32,
+}
+
 #[derive(Component, Debug, ConvertSaveload, Clone)]
 pub struct InBackpack {
     pub owner: Entity,
@@ -126,6 +362,12 @@ pub struct WantsToPickupItem {
     pub item: Entity,
 }
 
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct WantsToShoot {
+    pub weapon: Entity,
+    pub target: rltk::Point,
+}
+
 #[derive(Component, Debug, ConvertSaveload, Clone)]
 pub struct WantsToUseItem {
     pub item: Entity,
@@ -151,12 +393,27 @@ pub struct EntityMoved {}
 #[derive(Component, Serialize, Deserialize, Clone)]
 pub struct ParticleLifetime {
     pub lifetime_ms: f32,
+    pub animation: Option<ParticleAnimation>,
+    /// Turns wait for blocking particles to finish before carrying on
+    pub blocking: bool,
+}
+
+/// How a particle changes while it is alive: cycling through frames, and drifting across
+/// the map at a fixed velocity (in tiles per second).
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ParticleAnimation {
+    pub frames: Vec<rltk::FontCharType>,
+    pub frame_ms: f32,
+    pub velocity: (f32, f32),
+    pub origin: (i32, i32),
+    pub elapsed_ms: f32,
 }
 
 #[derive(PartialEq, Copy, Clone, Serialize, Deserialize)]
 pub enum EquipmentSlot {
     Melee,
     Shield,
+    Ranged,
 }
 
 #[derive(Component, Serialize, Deserialize, Clone)]
@@ -175,6 +432,21 @@ pub struct MeleePowerBonus {
     pub power: i32,
 }
 
+/// The shape an equipped melee item gives to its owner's weapon entity.
+#[derive(Component, ConvertSaveload, Clone)]
+pub struct Wieldable {
+    pub glyph: rltk::FontCharType,
+    pub fg: RGB,
+    pub power: i32,
+    pub reach: i32,
+    pub durability: i32,
+    pub max_durability: i32,
+    pub damage_type: DamageType,
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Broken {}
+
 #[derive(Component, ConvertSaveload, Clone)]
 pub struct DefenseBonus {
     pub defense: i32,
@@ -183,6 +455,241 @@ pub struct DefenseBonus {
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
 pub struct SingleActivation {}
 
+/// Not drawn until the player notices it
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Hidden {}
+
+/// A trap that wakes up every monster on the level
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct SoundsAlarm {}
+
+/// A trap that sends whoever sets it off somewhere else on the level
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Teleports {}
+
+/// What a monster is currently doing.
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Serialize, Deserialize)]
+pub enum Behaviour {
+    Asleep,
+    Idle,
+    Wander,
+    Hunt,
+    Search,
+    Flee,
+}
+
+impl Behaviour {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Behaviour::Asleep => "Asleep",
+            Behaviour::Idle => "Idle",
+            Behaviour::Wander => "Wandering",
+            Behaviour::Hunt => "Hunting",
+            Behaviour::Search => "Searching",
+            Behaviour::Flee => "Fleeing",
+        }
+    }
+}
+
+/// How much thought a monster gives to the player's weapon.
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Serialize, Deserialize)]
+pub enum Wits {
+    /// Walks straight at whatever is closest, weapon or not
+    Mindless,
+    /// Keeps out of the weapon's reach, and tries to get around behind it
+    Wary,
+    /// As wary, but also goes for the weapon to wrench it away
+    Cunning,
+}
+
+/// A monster's current behaviour, what it remembers, and the temperament that decides
+/// when it changes its mind.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Brain {
+    pub state: Behaviour,
+    pub wits: Wits,
+    /// Where the player was when the monster last knew
+    pub last_seen: Option<rltk::Point>,
+    pub wander_to: Option<rltk::Point>,
+    pub search_turns: i32,
+    /// How many turns it searches for a player it has lost track of
+    pub patience: i32,
+    /// Flees once its hp falls to this percentage of its maximum
+    pub flee_below: i32,
+    /// The percentage chance each turn that an idle monster sets off wandering
+    pub restlessness: i32,
+}
+
+impl Brain {
+    pub fn new(
+        state: Behaviour,
+        wits: Wits,
+        patience: i32,
+        flee_below: i32,
+        restlessness: i32,
+    ) -> Brain {
+        Brain {
+            state,
+            wits,
+            last_seen: None,
+            wander_to: None,
+            search_turns: 0,
+            patience,
+            flee_below,
+            restlessness,
+        }
+    }
+}
+
+/// Belongs to a pack that follows a leader, and fights together under the pack
+/// planner's orders. A leader is a member of its own pack.
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct PackMember {
+    pub leader: Entity,
+}
+
+/// The sides creatures take.
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Serialize, Deserialize)]
+pub enum Faction {
+    Player,
+    Undead,
+    Troglodytes,
+    Ogres,
+}
+
+/// How one creature feels about another.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum Reaction {
+    Hostile,
+    Neutral,
+    Friendly,
+}
+
+impl Reaction {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Reaction::Hostile => "Hostile",
+            Reaction::Neutral => "Neutral",
+            Reaction::Friendly => "Friendly",
+        }
+    }
+}
+
+impl Faction {
+    /// The reaction table: how members of this faction treat members of another.
+    pub fn reaction(self, other: Faction) -> Reaction {
+        if self == other {
+            return Reaction::Friendly;
+        }
+        match (self, other) {
+            (Faction::Player, _) => Reaction::Hostile,
+            (Faction::Undead, Faction::Player) => Reaction::Hostile,
+            (Faction::Troglodytes, Faction::Player) => Reaction::Hostile,
+            // Ogres leave the player be, but troglodytes are food
+            (Faction::Ogres, Faction::Troglodytes) => Reaction::Hostile,
+            _ => Reaction::Neutral,
+        }
+    }
+}
+
+/// Which faction a creature belongs to.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Allegiance {
+    pub faction: Faction,
+    /// Set once the player has attacked it, after which it is hostile to them whatever
+    /// its faction thinks
+    pub angered: bool,
+}
+
+impl Allegiance {
+    pub fn new(faction: Faction) -> Allegiance {
+        Allegiance { faction, angered: false }
+    }
+
+    pub fn reaction_to(&self, other: &Allegiance) -> Reaction {
+        if self.angered && other.faction == Faction::Player {
+            Reaction::Hostile
+        } else {
+            self.faction.reaction(other.faction)
+        }
+    }
+}
+
+/// The final boss, and how far through the fight it is.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Boss {
+    pub phase: i32,
+    /// Turns until it can start another area attack
+    pub attack_cooldown: i32,
+    /// Turns until it can raise more of the dead
+    pub summon_cooldown: i32,
+}
+
+/// An attack being wound up, which lands on every tile in `tiles` once `turns` have
+/// passed. The tiles are highlighted until then, so there's time to get out of the way.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Telegraph {
+    pub name: String,
+    pub tiles: Vec<rltk::Point>,
+    pub damage: i32,
+    pub damage_type: DamageType,
+    pub turns: i32,
+}
+
+/// The slow, heavy attacks a monster can wind up instead of just hitting.
+#[derive(PartialEq, Copy, Clone, Debug, Serialize, Deserialize)]
+pub enum WindUpKind {
+    /// A blow brought down on the tile its target is standing on
+    Smash,
+    /// A run in a straight line, hitting the first thing in the way
+    Charge,
+}
+
+impl WindUpKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            WindUpKind::Smash => "overhead smash",
+            WindUpKind::Charge => "charge",
+        }
+    }
+}
+
+/// A monster that sometimes winds up a heavy attack, with `chance` percent each turn it
+/// has the opening.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct WindUpAttack {
+    pub kind: WindUpKind,
+    pub chance: i32,
+}
+
+/// A wind-up attack in progress. Once `turns` have passed it becomes a `WantsToMelee`
+/// against whatever is then standing in its tiles; until then the tiles are highlighted.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct PendingAttack {
+    pub kind: WindUpKind,
+    pub tiles: Vec<rltk::Point>,
+    pub turns: i32,
+}
+
+/// Names the table a monster's loot is rolled from when it dies
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct LootTable {
+    pub table: String,
+}
+
+/// What is left of a creature, until it rots away
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Corpse {
+    pub of: String,
+    pub turns: i32,
+}
+
+/// Knows where the player is without seeing them, for a while
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Alerted {
+    pub turns: i32,
+}
+
 // Serialization helper code. We need to implement ConvertSaveLoad for each type that contains an
 // Entity.
 
@@ -201,9 +708,35 @@ pub struct Attribute {
     pub bonus: i32,
 }
 
+impl Attribute {
+    pub fn new(base: i32) -> Attribute {
+        Attribute { base, modifiers: 0, bonus: attr_bonus(base) }
+    }
+
+    /// Rolls a d20 plus this attribute's bonus against a difficulty
+    pub fn check(&self, rng: &mut RandomNumberGenerator, difficulty: i32) -> bool {
+        rng.roll_dice(1, 20) + self.bonus >= difficulty
+    }
+}
+
+/// The bonus (or penalty) an attribute value gives to rolls
+pub fn attr_bonus(value: i32) -> i32 {
+    (value - 10) / 2
+}
+
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
 pub struct Attributes {
     pub strength: Attribute,
     pub dexterity: Attribute,
     pub endurance: Attribute,
 }
+
+impl Attributes {
+    pub fn new(strength: i32, dexterity: i32, endurance: i32) -> Attributes {
+        Attributes {
+            strength: Attribute::new(strength),
+            dexterity: Attribute::new(dexterity),
+            endurance: Attribute::new(endurance),
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use super::{spawner::CORPSE_DECAY_TURNS, Corpse, Name, Renderable, RunState};
+use rltk::RGB;
+use specs::prelude::*;
+
+/// Rots corpses away a turn at a time. Halfway through they start to smell, and at the
+/// end there is nothing left.
+pub struct CorpseDecaySystem {}
+
+impl<'a> System<'a> for CorpseDecaySystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, RunState>,
+        WriteStorage<'a, Corpse>,
+        WriteStorage<'a, Name>,
+        WriteStorage<'a, Renderable>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (entities, runstate, mut corpses, mut names, mut renderables) = data;
+
+        if *runstate != RunState::MonsterTurn {
+            return;
+        }
+
+        for (entity, corpse) in (&entities, &mut corpses).join() {
+            corpse.turns -= 1;
+            if corpse.turns < 1 {
+                entities.delete(entity).expect("Unable to delete corpse");
+            } else if corpse.turns == CORPSE_DECAY_TURNS / 2 {
+                if let Some(name) = names.get_mut(entity) {
+                    name.name = format!("Rotting {} corpse", corpse.of);
+                }
+                if let Some(render) = renderables.get_mut(entity) {
+                    render.fg = RGB::named(rltk::OLIVE);
+                }
+            }
+        }
+    }
+}
@@ -1,16 +1,103 @@
-use super::{gamelog::GameLog, CombatStats, Name, Player, RunState, SufferDamage};
+use super::{
+    gamelog::GameLog, spawner, Boss, CombatStats, DamageType, Equipped, InBackpack, LootTable,
+    Name, Player, Position, Renderable, Resistant, RunState, SufferDamage, Vulnerable, WeaponStats,
+};
+use rltk::RGB;
 use specs::prelude::*;
 
+/// How well a type of damage works against whoever it lands on.
+#[derive(PartialEq, Copy, Clone)]
+pub enum Effectiveness {
+    Resisted,
+    Normal,
+    Vulnerable,
+}
+
+impl Effectiveness {
+    /// Works out the effectiveness against an entity, counting any armour it has equipped.
+    pub fn against(
+        damage_type: DamageType,
+        victim: Entity,
+        resistant: &ReadStorage<Resistant>,
+        vulnerable: &ReadStorage<Vulnerable>,
+        equipped: &ReadStorage<Equipped>,
+    ) -> Effectiveness {
+        let mut resists = resistant
+            .get(victim)
+            .is_some_and(|r| r.damage_types.contains(&damage_type));
+        for (resistance, equipped_by) in (resistant, equipped).join() {
+            if equipped_by.owner == victim && resistance.damage_types.contains(&damage_type) {
+                resists = true;
+            }
+        }
+        let weak = vulnerable
+            .get(victim)
+            .is_some_and(|v| v.damage_types.contains(&damage_type));
+
+        match (resists, weak) {
+            (true, false) => Effectiveness::Resisted,
+            (false, true) => Effectiveness::Vulnerable,
+            _ => Effectiveness::Normal,
+        }
+    }
+
+    pub fn apply(&self, amount: i32) -> i32 {
+        match self {
+            Effectiveness::Resisted => amount / 2,
+            Effectiveness::Normal => amount,
+            Effectiveness::Vulnerable => amount * 2,
+        }
+    }
+}
+
 pub struct DamageSystem {}
 
 impl<'a> System<'a> for DamageSystem {
-    type SystemData = (WriteStorage<'a, CombatStats>, WriteStorage<'a, SufferDamage>);
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        WriteExpect<'a, GameLog>,
+        WriteStorage<'a, CombatStats>,
+        WriteStorage<'a, SufferDamage>,
+        ReadStorage<'a, Resistant>,
+        ReadStorage<'a, Vulnerable>,
+        ReadStorage<'a, Equipped>,
+        ReadStorage<'a, Name>,
+    );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mut stats, mut damage) = data;
+        let (entities, mut log, mut stats, mut damage, resistant, vulnerable, equipped, names) =
+            data;
 
-        for (mut stats, damage) in (&mut stats, &damage).join() {
-            stats.hp -= damage.amount.iter().sum::<i32>();
+        for (entity, stats, damage) in (&entities, &mut stats, &damage).join() {
+            for (amount, damage_type) in damage.amount.iter() {
+                let effectiveness = Effectiveness::against(
+                    *damage_type,
+                    entity,
+                    &resistant,
+                    &vulnerable,
+                    &equipped,
+                );
+                let taken = effectiveness.apply(*amount);
+                if let Some(name) = names.get(entity) {
+                    match effectiveness {
+                        Effectiveness::Resisted => log.entries.push(format!(
+                            "{} resists the {} damage, taking only {} hp.",
+                            &name.name,
+                            damage_type.name(),
+                            taken
+                        )),
+                        Effectiveness::Vulnerable => log.entries.push(format!(
+                            "{} is vulnerable to {} damage, taking {} hp!",
+                            &name.name,
+                            damage_type.name(),
+                            taken
+                        )),
+                        Effectiveness::Normal => {}
+                    }
+                }
+                stats.hp -= taken;
+            }
         }
 
         damage.clear();
@@ -19,14 +106,22 @@ impl<'a> System<'a> for DamageSystem {
 
 pub fn delete_the_dead(ecs: &mut World) {
     let mut dead: Vec<Entity> = Vec::new();
+    let mut remains: Vec<(Position, String, RGB, Option<String>)> = Vec::new();
+    let mut boss_slain = false;
     // Using a scope to make the borrow checker happy
     {
         let combat_stats = ecs.read_storage::<CombatStats>();
         let players = ecs.read_storage::<Player>();
         let names = ecs.read_storage::<Name>();
+        let weapons = ecs.read_storage::<WeaponStats>();
+        let positions = ecs.read_storage::<Position>();
+        let renderables = ecs.read_storage::<Renderable>();
+        let loot_tables = ecs.read_storage::<LootTable>();
+        let bosses = ecs.read_storage::<Boss>();
         let entities = ecs.entities();
         let mut log = ecs.write_resource::<GameLog>();
-        for (entity, stats) in (&entities, &combat_stats).join() {
+        // Weapons don't die, they break, which is handled by the weapon system
+        for (entity, stats, _weapon) in (&entities, &combat_stats, !&weapons).join() {
             if stats.hp < 1 {
                 let player = players.get(entity);
                 match player {
@@ -35,6 +130,18 @@ pub fn delete_the_dead(ecs: &mut World) {
                         if let Some(victim_name) = victim_name {
                             log.entries.push(format!("{} is dead", &victim_name.name));
                         }
+                        if let (Some(pos), Some(victim_name)) = (positions.get(entity), victim_name)
+                        {
+                            remains.push((
+                                pos.clone(),
+                                victim_name.name.clone(),
+                                renderables
+                                    .get(entity)
+                                    .map_or(RGB::named(rltk::RED), |r| r.fg),
+                                loot_tables.get(entity).map(|l| l.table.clone()),
+                            ));
+                        }
+                        boss_slain |= bosses.get(entity).is_some();
                         dead.push(entity)
                     }
                     Some(_) => {
@@ -46,7 +153,49 @@ pub fn delete_the_dead(ecs: &mut World) {
         }
     }
 
+    // Whatever a monster was carrying falls to the floor where it died
+    {
+        let entities = ecs.entities();
+        let mut positions = ecs.write_storage::<Position>();
+        let mut backpack = ecs.write_storage::<InBackpack>();
+        let mut equipped = ecs.write_storage::<Equipped>();
+        let mut dropped: Vec<(Entity, Position)> = Vec::new();
+        for (item, owner) in (&entities, &backpack)
+            .join()
+            .map(|(item, pack)| (item, pack.owner))
+            .chain(
+                (&entities, &equipped)
+                    .join()
+                    .map(|(item, eq)| (item, eq.owner)),
+            )
+        {
+            if let (true, Some(pos)) = (dead.contains(&owner), positions.get(owner)) {
+                dropped.push((item, pos.clone()));
+            }
+        }
+        for (item, pos) in dropped {
+            backpack.remove(item);
+            equipped.remove(item);
+            positions
+                .insert(item, pos)
+                .expect("Unable to insert position");
+        }
+    }
+
     for victim in dead {
         ecs.delete_entity(victim).expect("Unable to delete");
     }
+
+    // Killing the boss wins the game
+    if boss_slain {
+        *ecs.write_resource::<RunState>() = RunState::ShowEnding;
+    }
+
+    // The dead leave a body behind, and drop whatever they were carrying
+    for (pos, name, fg, loot) in remains {
+        spawner::corpse(ecs, pos.x, pos.y, &name, fg);
+        if let Some(loot) = loot {
+            spawner::drop_loot(ecs, pos.x, pos.y, &loot);
+        }
+    }
 }
@@ -0,0 +1,19 @@
+use super::{Dodging, RunState};
+use specs::prelude::*;
+
+/// Closes evasion windows once the monsters have had their turn to swing at the dodger.
+pub struct DodgeSystem {}
+
+impl<'a> System<'a> for DodgeSystem {
+    type SystemData = (ReadExpect<'a, RunState>, WriteStorage<'a, Dodging>);
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (runstate, mut dodging) = data;
+
+        if *runstate != RunState::MonsterTurn {
+            return;
+        }
+
+        dodging.clear();
+    }
+}
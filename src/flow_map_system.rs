@@ -0,0 +1,324 @@
+use super::{Behaviour, Brain, CombatStats, Map, RunState, TileType};
+use rltk::Point;
+use specs::prelude::*;
+
+/// The cost of a straight step, in hundredths of a tile.
+const STRAIGHT_COST: i32 = 100;
+
+/// The cost of a diagonal step, in hundredths of a tile.
+const DIAGONAL_COST: i32 = 145;
+
+/// Enough buckets that a step never wraps round onto the bucket it came from.
+const RING_SIZE: usize = DIAGONAL_COST as usize + 1;
+
+/// How much more a fleeing monster cares about getting away than about the distance it
+/// covers, as a percentage. Above 100, fleeing monsters will run past the player to
+/// reach open ground rather than get cornered.
+const FLEE_WEIGHT: i32 = 120;
+
+/// Distances from a set of starting tiles to everywhere else on the map. Anything
+/// following one just keeps stepping downhill.
+#[derive(Default)]
+pub struct FlowMap {
+    values: Vec<i32>,
+}
+
+impl FlowMap {
+    /// Spreads out from the starting tiles, each of which can start at its own value.
+    ///
+    /// Steps only ever cost one of two small amounts, so rather than a priority queue this
+    /// keeps a ring of buckets, one per distance, and works through them in order.
+    pub fn build(map: &Map, starts: &[(usize, i32)]) -> FlowMap {
+        let mut values = vec![i32::MAX; map.tiles.len()];
+        let mut seeds: Vec<(i32, usize)> =
+            starts.iter().map(|(idx, value)| (*value, *idx)).collect();
+        seeds.sort_unstable();
+        let mut seeds = seeds.into_iter().peekable();
+
+        let mut ring: Vec<Vec<usize>> = vec![Vec::new(); RING_SIZE];
+        let slot = |value: i32| value.rem_euclid(RING_SIZE as i32) as usize;
+        let mut queued = 0;
+        let mut current = match seeds.peek() {
+            None => return FlowMap { values },
+            Some((value, _idx)) => *value,
+        };
+        loop {
+            while let Some((value, idx)) = seeds.next_if(|(value, _idx)| *value <= current) {
+                if value < values[idx] {
+                    values[idx] = value;
+                    ring[slot(value)].push(idx);
+                    queued += 1;
+                }
+            }
+
+            let bucket = std::mem::take(&mut ring[slot(current)]);
+            queued -= bucket.len();
+            for idx in bucket {
+                // Tiles can be queued more than once; only the shortest distance counts
+                if values[idx] != current {
+                    continue;
+                }
+                for (next, cost) in exits(map, idx) {
+                    if current + cost < values[next] {
+                        values[next] = current + cost;
+                        ring[slot(current + cost)].push(next);
+                        queued += 1;
+                    }
+                }
+            }
+
+            current = match (queued, seeds.peek()) {
+                (0, None) => break,
+                (0, Some((value, _idx))) => *value,
+                _ => current + 1,
+            };
+        }
+
+        FlowMap { values }
+    }
+
+    /// How far a tile is from the nearest start, if it can be reached at all.
+    pub fn distance(&self, idx: usize) -> Option<i32> {
+        match self.values.get(idx) {
+            Some(value) if *value != i32::MAX => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// The free neighbouring tile that is furthest downhill from `idx`, leaving out any
+    /// tiles in `avoid`.
+    pub fn downhill(&self, map: &Map, idx: usize, avoid: &[Point]) -> Option<usize> {
+        let mut best = self.distance(idx)?;
+        let mut step = None;
+        for (next, _cost) in exits(map, idx) {
+            let point = Point::new(next as i32 % map.width, next as i32 / map.width);
+            if map.blocked[next] || avoid.contains(&point) {
+                continue;
+            }
+            if let Some(value) = self.distance(next) {
+                if value < best {
+                    best = value;
+                    step = Some(next);
+                }
+            }
+        }
+        step
+    }
+}
+
+/// The walkable tiles next to `idx`, and what it costs to step onto each.
+fn exits(map: &Map, idx: usize) -> impl Iterator<Item = (usize, i32)> + '_ {
+    let (x, y) = (idx as i32 % map.width, idx as i32 / map.width);
+    [
+        (-1, -1),
+        (0, -1),
+        (1, -1),
+        (-1, 0),
+        (1, 0),
+        (-1, 1),
+        (0, 1),
+        (1, 1),
+    ]
+    .into_iter()
+    .filter(move |(dx, dy)| {
+        let (nx, ny) = (x + dx, y + dy);
+        nx > 0 && nx < map.width - 1 && ny > 0 && ny < map.height - 1
+    })
+    .map(move |(dx, dy)| {
+        let cost = if dx != 0 && dy != 0 {
+            DIAGONAL_COST
+        } else {
+            STRAIGHT_COST
+        };
+        (map.xy_idx(x + dx, y + dy), cost)
+    })
+    .filter(move |(next, _cost)| map.is_walkable(*next))
+}
+
+/// The flow maps every monster shares: towards the player, away from the player, and
+/// towards the way down.
+#[derive(Default)]
+pub struct FlowMaps {
+    pub to_player: FlowMap,
+    pub flee: FlowMap,
+    pub to_stairs: FlowMap,
+}
+
+impl FlowMaps {
+    /// Builds the map towards the player, and the maps for running away if anything
+    /// needs them.
+    pub fn build(map: &Map, player: Point, fleeing: bool) -> FlowMaps {
+        let to_player = FlowMap::build(map, &[(map.xy_idx(player.x, player.y), 0)]);
+        if !fleeing {
+            return FlowMaps { to_player, ..Default::default() };
+        }
+
+        // Fleeing is chasing in reverse, then letting the values settle so that monsters
+        // run for open ground instead of into corners
+        let flee_starts: Vec<(usize, i32)> = (0..map.tiles.len())
+            .filter_map(|idx| {
+                to_player
+                    .distance(idx)
+                    .map(|d| (idx, -d * FLEE_WEIGHT / 100))
+            })
+            .collect();
+        let flee = FlowMap::build(map, &flee_starts);
+
+        let stairs: Vec<(usize, i32)> = map
+            .tiles
+            .iter()
+            .enumerate()
+            .filter(|(_idx, tile)| **tile == TileType::DownStairs)
+            .map(|(idx, _tile)| (idx, 0))
+            .collect();
+        let to_stairs = FlowMap::build(map, &stairs);
+
+        FlowMaps { to_player, flee, to_stairs }
+    }
+}
+
+/// Rebuilds the shared flow maps once at the start of each monster turn, so that
+/// however many monsters there are, none of them has to search for a path of its own.
+/// The maps for running away are only built on turns when something is fleeing.
+pub struct FlowMapSystem {}
+
+impl<'a> System<'a> for FlowMapSystem {
+    type SystemData = (
+        ReadExpect<'a, Map>,
+        ReadExpect<'a, Point>,
+        ReadExpect<'a, RunState>,
+        WriteExpect<'a, FlowMaps>,
+        ReadStorage<'a, Brain>,
+        ReadStorage<'a, CombatStats>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (map, player_pos, runstate, mut flow_maps, brains, combat_stats) = data;
+
+        if *runstate != RunState::MonsterTurn {
+            return;
+        }
+
+        let fleeing = (&brains, &combat_stats).join().any(|(brain, stats)| {
+            brain.state == Behaviour::Flee || stats.hp * 100 <= stats.max_hp * brain.flee_below
+        });
+        *flow_maps = FlowMaps::build(&map, *player_pos, fleeing);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rltk::RandomNumberGenerator;
+
+    /// A walled-in patch of floor in the corner of the map, with some pillars scattered
+    /// over it and a closed-off cell that nothing can reach.
+    fn test_map(seed: u64) -> Map {
+        let mut map = Map::new(1);
+        let mut rng = RandomNumberGenerator::seeded(seed);
+        for y in 1..13 {
+            for x in 1..21 {
+                let idx = map.xy_idx(x, y);
+                map.tiles[idx] = if rng.roll_dice(1, 4) == 1 {
+                    TileType::StoneWall
+                } else {
+                    TileType::Floor
+                };
+            }
+        }
+        for (x, y) in [
+            (24, 4),
+            (25, 4),
+            (26, 4),
+            (24, 5),
+            (26, 5),
+            (24, 6),
+            (25, 6),
+            (26, 6),
+        ] {
+            let idx = map.xy_idx(x, y);
+            map.tiles[idx] = TileType::StoneWall;
+        }
+        let idx = map.xy_idx(25, 5);
+        map.tiles[idx] = TileType::Floor;
+        map
+    }
+
+    /// The same distances worked out the slow way: keep relaxing every step until
+    /// nothing changes.
+    fn brute_force(map: &Map, starts: &[(usize, i32)]) -> Vec<i32> {
+        let mut values = vec![i32::MAX; map.tiles.len()];
+        for (idx, value) in starts {
+            values[*idx] = i32::min(values[*idx], *value);
+        }
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for idx in 0..map.tiles.len() {
+                if values[idx] == i32::MAX {
+                    continue;
+                }
+                for (next, cost) in exits(map, idx) {
+                    if values[idx] + cost < values[next] {
+                        values[next] = values[idx] + cost;
+                        changed = true;
+                    }
+                }
+            }
+        }
+        values
+    }
+
+    fn assert_matches(map: &Map, starts: &[(usize, i32)]) {
+        let flow = FlowMap::build(map, starts);
+        let expected = brute_force(map, starts);
+        for (idx, value) in expected.iter().enumerate() {
+            let expected = if *value == i32::MAX {
+                None
+            } else {
+                Some(*value)
+            };
+            assert_eq!(flow.distance(idx), expected, "tile {}", idx);
+        }
+    }
+
+    #[test]
+    fn matches_brute_force_from_one_start() {
+        for seed in 0..20 {
+            let map = test_map(seed);
+            let start = (1..map.tiles.len())
+                .find(|idx| map.is_walkable(*idx))
+                .unwrap();
+            assert_matches(&map, &[(start, 0)]);
+        }
+    }
+
+    #[test]
+    fn matches_brute_force_from_negative_starts() {
+        for seed in 0..20 {
+            let map = test_map(seed);
+            let start = (1..map.tiles.len())
+                .find(|idx| map.is_walkable(*idx))
+                .unwrap();
+            let to_start = FlowMap::build(&map, &[(start, 0)]);
+            let starts: Vec<(usize, i32)> = (0..map.tiles.len())
+                .filter_map(|idx| {
+                    to_start
+                        .distance(idx)
+                        .map(|d| (idx, -d * FLEE_WEIGHT / 100))
+                })
+                .collect();
+            assert_matches(&map, &starts);
+        }
+    }
+
+    #[test]
+    fn leaves_sealed_tiles_unreached() {
+        let map = test_map(0);
+        let start = (1..map.tiles.len())
+            .find(|idx| map.is_walkable(*idx))
+            .unwrap();
+        let flow = FlowMap::build(&map, &[(start, 0)]);
+        assert_eq!(flow.distance(map.xy_idx(25, 5)), None);
+    }
+}
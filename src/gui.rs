@@ -1,6 +1,13 @@
 use super::{
-    gamelog::GameLog, rex_assets::RexAssets, CombatStats, Equipped, InBackpack, Map, Name, Player,
-    Position, RunState, State, Viewshed,
+    damage_system::Effectiveness,
+    gamelog::GameLog,
+    ranged_combat_system::{line_of_fire, own_weapon_points},
+    rex_assets::RexAssets,
+    stamina_system::StaminaAction,
+    telegraph_system::Telegraphed,
+    Allegiance, Brain, CombatStats, DamageType, Equipped, Exhausted, Hidden, InBackpack,
+    InflictsDamage, Map, Name, PendingAttack, Player, Position, Resistant, RunState, State,
+    StatusEffects, StatusKind, Telegraph, Viewshed, Vulnerable, WeaponStats, Wieldable,
 };
 use rltk::{Point, Rect, Rltk, VirtualKeyCode, RGB};
 use specs::prelude::*;
@@ -11,7 +18,11 @@ pub fn draw_ui(ecs: &World, ctx: &mut Rltk) {
 
     let combat_stats = ecs.read_storage::<CombatStats>();
     let players = ecs.read_storage::<Player>();
-    for (_player, stats) in (&players, &combat_stats).join() {
+    let exhausted = ecs.read_storage::<Exhausted>();
+    let statuses = ecs.read_storage::<StatusEffects>();
+    let runstate = ecs.fetch::<RunState>();
+    let entities = ecs.entities();
+    for (player, _player, stats) in (&entities, &players, &combat_stats).join() {
         let health = format!(" HP: {} / {} ", stats.hp, stats.max_hp);
         ctx.print_color(12, 43, RGB::named(rltk::YELLOW), (40, 0, 0), &health);
 
@@ -24,7 +35,12 @@ pub fn draw_ui(ecs: &World, ctx: &mut Rltk) {
             RGB::named(rltk::RED),
             RGB::named(rltk::BLACK),
         );
-        let stamina = format!(" SP: {} / {} ", stats.stamina, stats.max_stamina);
+        let projected_cost = StaminaAction::for_runstate(*runstate).map_or(0, |a| a.cost());
+        let stamina = if projected_cost > 0 {
+            format!(" SP: {} / {} (-{}) ", stats.stamina, stats.max_stamina, projected_cost)
+        } else {
+            format!(" SP: {} / {} ", stats.stamina, stats.max_stamina)
+        };
         ctx.print_color(12, 44, RGB::named(rltk::YELLOW), (40, 0, 0), &stamina);
 
         ctx.draw_bar_horizontal(
@@ -36,6 +52,36 @@ pub fn draw_ui(ecs: &World, ctx: &mut Rltk) {
             RGB::named(rltk::BLUE),
             RGB::named(rltk::BLACK),
         );
+
+        // Highlight the part of the bar the selected action would use up
+        if projected_cost > 0 {
+            let fill = |n: i32| (n as f32 / stats.max_stamina as f32 * 51.0) as i32;
+            let from = fill(i32::max(0, stats.stamina - projected_cost));
+            let to = fill(stats.stamina);
+            let colour = if projected_cost > stats.stamina {
+                RGB::named(rltk::RED)
+            } else {
+                RGB::named(rltk::YELLOW)
+            };
+            for x in from + 1..=to {
+                ctx.set(28 + x, 44, colour, RGB::named(rltk::BLACK), rltk::to_cp437('▓'));
+            }
+        }
+
+        if exhausted.get(player).is_some() {
+            ctx.print_color(2, 44, RGB::named(rltk::ORANGE), (40, 0, 0), "EXHAUSTED");
+        }
+
+        // Active status effects, right-aligned just above the panel
+        if let Some(statuses) = statuses.get(player) {
+            let mut x = 79;
+            for effect in statuses.effects.iter().rev() {
+                let label = format!("{}({})", effect.kind.name(), effect.turns);
+                x -= label.len() as i32;
+                ctx.print_color(x, 42, status_colour(effect.kind), RGB::named(rltk::BLACK), &label);
+                x -= 1;
+            }
+        }
     }
 
     let map = ecs.fetch::<Map>();
@@ -51,12 +97,51 @@ pub fn draw_ui(ecs: &World, ctx: &mut Rltk) {
         y -= 1;
     }
 
+    draw_telegraphs(ecs, ctx);
+
     // Draw mouse cursor
     let mouse_pos = ctx.mouse_pos();
     ctx.set_bg(mouse_pos.0, mouse_pos.1, RGB::named(rltk::MAGENTA));
     draw_tooltips(ecs, ctx);
 }
 
+/// Warns about the tiles attacks that are winding up are about to land on.
+fn draw_telegraphs(ecs: &World, ctx: &mut Rltk) {
+    let map = ecs.fetch::<Map>();
+    let telegraphs = ecs.read_storage::<Telegraph>();
+    let pending_attacks = ecs.read_storage::<PendingAttack>();
+    let warned = telegraphs
+        .join()
+        .map(|telegraph| telegraph.tiles())
+        .chain(pending_attacks.join().map(|attack| attack.tiles()));
+    for tile in warned.flatten() {
+        if map.visible_tiles[map.xy_idx(tile.x, tile.y)] {
+            ctx.set_bg(tile.x, tile.y, RGB::named(rltk::DARK_RED));
+        }
+    }
+}
+
+fn status_colour(kind: StatusKind) -> RGB {
+    match kind {
+        StatusKind::Poison => RGB::named(rltk::GREEN),
+        StatusKind::Bleeding => RGB::named(rltk::RED),
+        StatusKind::Stun => RGB::named(rltk::YELLOW),
+        StatusKind::Slow => RGB::named(rltk::CYAN),
+        StatusKind::Haste => RGB::named(rltk::ORANGE),
+        StatusKind::Regeneration => RGB::named(rltk::MAGENTA),
+        StatusKind::Confusion => RGB::named(rltk::PINK),
+        StatusKind::Immobilised => RGB::named(rltk::GREY),
+    }
+}
+
+fn damage_type_list(damage_types: &[DamageType]) -> String {
+    damage_types
+        .iter()
+        .map(|t| t.name())
+        .collect::<Vec<&str>>()
+        .join(", ")
+}
+
 fn draw_tooltips(ecs: &World, ctx: &mut Rltk) {
     let map = ecs.fetch::<Map>();
     let names = ecs.read_storage::<Name>();
@@ -66,11 +151,71 @@ fn draw_tooltips(ecs: &World, ctx: &mut Rltk) {
     if mouse_pos.0 >= map.width || mouse_pos.1 >= map.height {
         return;
     }
+    let entities = ecs.entities();
+    let combat_stats = ecs.read_storage::<CombatStats>();
+    let weapon_stats = ecs.read_storage::<WeaponStats>();
+    let wieldables = ecs.read_storage::<Wieldable>();
+    let inflicts_damage = ecs.read_storage::<InflictsDamage>();
+    let resistant = ecs.read_storage::<Resistant>();
+    let vulnerable = ecs.read_storage::<Vulnerable>();
+    let brains = ecs.read_storage::<Brain>();
+    let allegiances = ecs.read_storage::<Allegiance>();
+    let equipped = ecs.read_storage::<Equipped>();
+    let player_entity = ecs.fetch::<Entity>();
+    let player_weapon = (&weapon_stats, &names)
+        .join()
+        .find(|(w, _)| w.owner == *player_entity);
+
     let mut tooltip: Vec<String> = Vec::new();
-    for (name, position) in (&names, &positions).join() {
+    let hidden = ecs.read_storage::<Hidden>();
+    for (entity, name, position, _hidden) in (&entities, &names, &positions, !&hidden).join() {
         let idx = map.xy_idx(position.x, position.y);
         if position.x == mouse_pos.0 && position.y == mouse_pos.1 && map.visible_tiles[idx] {
             tooltip.push(name.name.to_string());
+
+            let deals = wieldables
+                .get(entity)
+                .map(|w| w.damage_type)
+                .or_else(|| weapon_stats.get(entity).map(|w| w.damage_type))
+                .or_else(|| inflicts_damage.get(entity).map(|d| d.damage_type));
+            if let Some(deals) = deals {
+                tooltip.push(format!(" Deals {}", deals.name()));
+            }
+            if let Some(brain) = brains.get(entity) {
+                tooltip.push(format!(" {}", brain.state.name()));
+            }
+            if let (Some(theirs), Some(mine)) =
+                (allegiances.get(entity), allegiances.get(*player_entity))
+            {
+                if entity != *player_entity {
+                    tooltip.push(format!(" {}", theirs.reaction_to(mine).name()));
+                }
+            }
+            if let Some(resists) = resistant.get(entity) {
+                tooltip.push(format!(" Resists {}", damage_type_list(&resists.damage_types)));
+            }
+            if let Some(weak) = vulnerable.get(entity) {
+                tooltip.push(format!(" Weak to {}", damage_type_list(&weak.damage_types)));
+            }
+
+            // How well the player's weapon would do against this
+            if combat_stats.get(entity).is_some() && weapon_stats.get(entity).is_none() {
+                if let Some((weapon, weapon_name)) = player_weapon {
+                    let effectiveness = Effectiveness::against(
+                        weapon.damage_type,
+                        entity,
+                        &resistant,
+                        &vulnerable,
+                        &equipped,
+                    );
+                    let verdict = match effectiveness {
+                        Effectiveness::Resisted => "resisted",
+                        Effectiveness::Normal => "normal",
+                        Effectiveness::Vulnerable => "effective",
+                    };
+                    tooltip.push(format!(" {}: {}", weapon_name.name, verdict));
+                }
+            }
         }
     }
 
@@ -354,6 +499,19 @@ pub fn remove_item_menu(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Opti
     }
 }
 
+/// The tiles a shot from the player towards `target` would fly through.
+pub fn targeting_path(ecs: &World, target: Point) -> Vec<Point> {
+    let player_entity = ecs.fetch::<Entity>();
+    let player_pos = ecs.fetch::<Point>();
+    let map = ecs.fetch::<Map>();
+    let pass_through = own_weapon_points(
+        *player_entity,
+        &ecs.read_storage::<WeaponStats>(),
+        &ecs.read_storage::<Position>(),
+    );
+    line_of_fire(&map, *player_pos, target, &pass_through)
+}
+
 pub fn ranged_target(
     gs: &mut State,
     ctx: &mut Rltk,
@@ -390,6 +548,16 @@ pub fn ranged_target(
         }
     }
     if valid_target {
+        // Show where the shot would actually go, and where it would stop
+        let path = targeting_path(&gs.ecs, Point::new(mouse_pos.0, mouse_pos.1));
+        for point in path.iter() {
+            ctx.set_bg(point.x, point.y, RGB::named(rltk::YELLOW));
+        }
+        if let Some(end) = path.last() {
+            if end.x != mouse_pos.0 || end.y != mouse_pos.1 {
+                ctx.set_bg(end.x, end.y, RGB::named(rltk::ORANGE));
+            }
+        }
         ctx.set_bg(mouse_pos.0, mouse_pos.1, RGB::named(rltk::CYAN));
         if ctx.left_click {
             return (ItemMenuResult::Selected, Some(Point::new(mouse_pos.0, mouse_pos.1)));
@@ -646,14 +814,14 @@ pub fn show_ending(gs: &mut State, ctx: &mut Rltk) -> bool {
         23,
         RGB::named(rltk::WHITE),
         RGB::named(rltk::BLACK),
-        "Turns out you were after an amulet",
+        "Koschei the Deathless is dead at",
     );
     ctx.print_color(
         41,
         24,
         RGB::named(rltk::WHITE),
         RGB::named(rltk::BLACK),
-        "the whole time, and you got it.",
+        "last, and the amulet he guarded",
     );
 
     ctx.print_color(
@@ -661,7 +829,7 @@ pub fn show_ending(gs: &mut State, ctx: &mut Rltk) -> bool {
         25,
         RGB::named(rltk::WHITE),
         RGB::named(rltk::BLACK),
-        "(offscreen). Well done.",
+        "is yours. Well done.",
     );
 
     match ctx.key {
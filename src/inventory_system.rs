@@ -1,7 +1,12 @@
 use super::{
-    gamelog::GameLog, AreaOfEffect, CombatStats, Confusion, Consumable, Equippable, Equipped,
-    InBackpack, InflictsDamage, Map, Name, Position, ProvidesHealing, SufferDamage,
-    WantsToDropItem, WantsToPickupItem, WantsToRemoveItem, WantsToUseItem,
+    gamelog::GameLog,
+    melee_combat_system::provoke,
+    noise_system::{NoiseKind, Noises},
+    particle_system::{projectile_glyph, ParticleBuilder},
+    Allegiance, Ammo, AreaOfEffect, Broken, CombatStats, Consumable, Equippable, Equipped,
+    InBackpack, InflictsDamage, InflictsStatus, Map, Name, Position, ProvidesHealing,
+    ProvidesRepair, StatusEffects, SufferDamage, WantsToDropItem, WantsToPickupItem,
+    WantsToRemoveItem, WantsToUseItem, WeaponStats, Wieldable,
 };
 use specs::prelude::*;
 
@@ -10,29 +15,60 @@ pub struct ItemCollectionSystem {}
 impl<'a> System<'a> for ItemCollectionSystem {
     #[allow(clippy::type_complexity)]
     type SystemData = (
+        Entities<'a>,
         ReadExpect<'a, Entity>,
         WriteExpect<'a, GameLog>,
         WriteStorage<'a, WantsToPickupItem>,
         WriteStorage<'a, Position>,
-        ReadStorage<'a, Name>,
+        WriteStorage<'a, Name>,
         WriteStorage<'a, InBackpack>,
+        WriteStorage<'a, Ammo>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (player_entity, mut gamelog, mut wants_pickup, mut positions, names, mut backpack) =
-            data;
+        let (
+            entities,
+            player_entity,
+            mut gamelog,
+            mut wants_pickup,
+            mut positions,
+            mut names,
+            mut backpack,
+            mut ammo,
+        ) = data;
 
         for pickup in wants_pickup.join() {
-            positions.remove(pickup.item);
-            backpack
-                .insert(pickup.item, InBackpack { owner: pickup.collected_by })
-                .expect("Unable to insert backpack entry");
-
             if pickup.collected_by == *player_entity {
                 gamelog
                     .entries
                     .push(format!("You pick up the {}.", names.get(pickup.item).unwrap().name));
             }
+
+            // Ammunition goes into a bundle of the same kind if there already is one
+            let picked_up = ammo.get(pickup.item).cloned();
+            if let Some(picked_up) = picked_up {
+                let bundle = (&entities, &backpack)
+                    .join()
+                    .find(|(e, pack)| {
+                        pack.owner == pickup.collected_by
+                            && ammo.get(*e).is_some_and(|a| a.kind == picked_up.kind)
+                    })
+                    .map(|(e, _pack)| e);
+                if let Some(bundle) = bundle {
+                    let merged = ammo.get_mut(bundle).unwrap();
+                    merged.count += picked_up.count;
+                    if let Some(name) = names.get_mut(bundle) {
+                        name.name = merged.kind.bundle_name(merged.count);
+                    }
+                    entities.delete(pickup.item).expect("Unable to delete ammo");
+                    continue;
+                }
+            }
+
+            positions.remove(pickup.item);
+            backpack
+                .insert(pickup.item, InBackpack { owner: pickup.collected_by })
+                .expect("Unable to insert backpack entry");
         }
 
         wants_pickup.clear();
@@ -49,17 +85,26 @@ impl<'a> System<'a> for ItemUseSystem {
         ReadExpect<'a, Map>,
         Entities<'a>,
         WriteStorage<'a, WantsToUseItem>,
-        ReadStorage<'a, Name>,
+        WriteStorage<'a, Name>,
         ReadStorage<'a, Consumable>,
         ReadStorage<'a, ProvidesHealing>,
         ReadStorage<'a, InflictsDamage>,
         WriteStorage<'a, CombatStats>,
         WriteStorage<'a, SufferDamage>,
         ReadStorage<'a, AreaOfEffect>,
-        WriteStorage<'a, Confusion>,
+        ReadStorage<'a, InflictsStatus>,
+        WriteStorage<'a, StatusEffects>,
         ReadStorage<'a, Equippable>,
         WriteStorage<'a, Equipped>,
         WriteStorage<'a, InBackpack>,
+        WriteStorage<'a, Broken>,
+        ReadStorage<'a, ProvidesRepair>,
+        WriteStorage<'a, Wieldable>,
+        ReadStorage<'a, WeaponStats>,
+        ReadStorage<'a, Position>,
+        WriteExpect<'a, ParticleBuilder>,
+        WriteExpect<'a, Noises>,
+        WriteStorage<'a, Allegiance>,
     );
 
     #[allow(clippy::cognitive_complexity)]
@@ -70,29 +115,58 @@ impl<'a> System<'a> for ItemUseSystem {
             map,
             entities,
             mut wants_use,
-            names,
+            mut names,
             consumables,
             healing,
             inflict_damage,
             mut combat_stats,
             mut suffer_damage,
             aoe,
-            mut confused,
+            inflicts_status,
+            mut statuses,
             equippable,
             mut equipped,
             mut backpack,
+            mut broken,
+            repairing,
+            mut wieldables,
+            weapon_stats,
+            positions,
+            mut particle_builder,
+            mut noises,
+            mut allegiances,
         ) = data;
 
         for (entity, useitem) in (&entities, &wants_use).join() {
             let mut used_item = true;
+            let user_name = names
+                .get(entity)
+                .map_or("Something".to_string(), |n| n.name.clone());
 
-            // Targeting
+            // Targeting: anything used without a target is used on whoever is using it
             let mut targets: Vec<Entity> = Vec::new();
             match useitem.target {
                 None => {
-                    targets.push(*player_entity);
+                    targets.push(entity);
                 }
                 Some(target) => {
+                    // Whatever is used at range flies there first
+                    let mut arrival = 0.0;
+                    if let Some(pos) = positions.get(entity) {
+                        let from = rltk::Point::new(pos.x, pos.y);
+                        let path: Vec<rltk::Point> =
+                            rltk::line2d(rltk::LineAlg::Bresenham, from, target)
+                                .into_iter()
+                                .filter(|p| *p != from)
+                                .collect();
+                        arrival = particle_builder.projectile(
+                            &path,
+                            rltk::RGB::named(rltk::MAGENTA),
+                            projectile_glyph(target.x - from.x, target.y - from.y),
+                            0.0,
+                        );
+                    }
+
                     let area_effect = aoe.get(useitem.item);
                     match area_effect {
                         None => {
@@ -109,6 +183,13 @@ impl<'a> System<'a> for ItemUseSystem {
                             blast_tiles.retain(|p| {
                                 p.x > 0 && p.x < map.width - 1 && p.y > 0 && p.y < map.height - 1
                             });
+                            particle_builder.blast(
+                                target,
+                                &blast_tiles,
+                                rltk::RGB::named(rltk::ORANGE),
+                                arrival,
+                            );
+                            noises.make(target, NoiseKind::Blast);
                             for tile_idx in blast_tiles.iter() {
                                 let idx = map.xy_idx(tile_idx.x, tile_idx.y);
                                 for mob in map.tile_content[idx].iter() {
@@ -121,7 +202,17 @@ impl<'a> System<'a> for ItemUseSystem {
             }
 
             // If it is equippable, then we want to equip it - and unequip whatever else was in that slot
-            let item_equippable = equippable.get(useitem.item);
+            let mut item_equippable = equippable.get(useitem.item);
+            if item_equippable.is_some() && broken.get(useitem.item).is_some() {
+                // Broken weapons have to be repaired before they can be wielded again
+                item_equippable = None;
+                if entity == *player_entity {
+                    gamelog.entries.push(format!(
+                        "The {} needs repairing before it can be used.",
+                        names.get(useitem.item).unwrap().name
+                    ));
+                }
+            }
             match item_equippable {
                 None => {}
                 Some(can_equip) => {
@@ -184,6 +275,49 @@ impl<'a> System<'a> for ItemUseSystem {
                 }
             }
 
+            // If it repairs, mend every weapon the user is carrying or wielding
+            let item_repairs = repairing.get(useitem.item);
+            match item_repairs {
+                None => {}
+                Some(repair) => {
+                    for (weapon, weapon_combat) in (&weapon_stats, &mut combat_stats).join() {
+                        if weapon.owner == entity {
+                            weapon_combat.hp = i32::min(
+                                weapon_combat.max_hp,
+                                weapon_combat.hp + repair.repair_amount,
+                            );
+                        }
+                    }
+
+                    let mut mended: Vec<Entity> = Vec::new();
+                    for (item_entity, wieldable, _broken) in
+                        (&entities, &mut wieldables, &broken).join()
+                    {
+                        let in_pack = backpack.get(item_entity).is_some_and(|b| b.owner == entity);
+                        if in_pack {
+                            wieldable.durability = i32::min(
+                                wieldable.max_durability,
+                                wieldable.durability + repair.repair_amount,
+                            );
+                            mended.push(item_entity);
+                        }
+                    }
+                    for item_entity in mended.iter() {
+                        broken.remove(*item_entity);
+                        if let Some(name) = names.get_mut(*item_entity) {
+                            name.name = name.name.trim_start_matches("Broken ").to_string();
+                        }
+                    }
+
+                    if entity == *player_entity {
+                        gamelog.entries.push(format!(
+                            "You use the {}, repairing your weapons.",
+                            names.get(useitem.item).unwrap().name
+                        ));
+                    }
+                }
+            }
+
             // If it inflicts damage, apply it to the target cell
             let item_damages = inflict_damage.get(useitem.item);
             match item_damages {
@@ -191,13 +325,33 @@ impl<'a> System<'a> for ItemUseSystem {
                 Some(damage) => {
                     used_item = false;
                     for mob in targets.iter() {
-                        SufferDamage::new_damage(&mut suffer_damage, *mob, damage.damage);
+                        if combat_stats.get(*mob).is_none() {
+                            continue;
+                        }
+                        SufferDamage::new_damage(
+                            &mut suffer_damage,
+                            *mob,
+                            damage.damage,
+                            damage.damage_type,
+                        );
+                        let mob_name = names
+                            .get(*mob)
+                            .map_or("something".to_string(), |n| n.name.clone());
+                        let item_name = names
+                            .get(useitem.item)
+                            .map_or("something".to_string(), |n| n.name.clone());
                         if entity == *player_entity {
-                            let mob_name = names.get(*mob).unwrap();
-                            let item_name = names.get(useitem.item).unwrap();
                             gamelog.entries.push(format!(
                                 "You use {} on {}, inflicting {} hp.",
-                                item_name.name, mob_name.name, damage.damage
+                                item_name, mob_name, damage.damage
+                            ));
+                            if provoke(&mut allegiances, *mob, *player_entity) {
+                                gamelog.entries.push(format!("{} turns on you!", mob_name));
+                            }
+                        } else {
+                            gamelog.entries.push(format!(
+                                "{} uses {} on {}, inflicting {} hp.",
+                                user_name, item_name, mob_name, damage.damage
                             ));
                         }
 
@@ -206,33 +360,52 @@ impl<'a> System<'a> for ItemUseSystem {
                 }
             }
 
-            // Can it pass along confusion? Note the use of scopes to escape from the borrow checker!
-            let mut add_confusion = Vec::new();
-            {
-                let causes_confusion = confused.get(useitem.item);
-                match causes_confusion {
-                    None => {}
-                    Some(confusion) => {
-                        used_item = false;
-                        for mob in targets.iter() {
-                            add_confusion.push((*mob, confusion.turns));
-                            if entity == *player_entity {
-                                let mob_name = names.get(*mob).unwrap();
-                                let item_name = names.get(useitem.item).unwrap();
-                                gamelog.entries.push(format!(
-                                    "You use {} on {}, confusing them.",
-                                    item_name.name, mob_name.name
-                                ));
-                            }
+            // Can it pass along a status effect? Weapons only pass theirs on when they hit
+            let mut item_status = inflicts_status.get(useitem.item);
+            if equippable.get(useitem.item).is_some() {
+                item_status = None;
+            }
+            match item_status {
+                None => {}
+                Some(status) => {
+                    used_item = false;
+                    for mob in targets.iter() {
+                        if combat_stats.get(*mob).is_none() {
+                            continue;
                         }
+                        StatusEffects::add_effect(
+                            &mut statuses,
+                            *mob,
+                            status.kind,
+                            status.turns,
+                            status.potency,
+                        );
+                        let mob_name = names
+                            .get(*mob)
+                            .map_or("something".to_string(), |n| n.name.clone());
+                        let item_name = names
+                            .get(useitem.item)
+                            .map_or("something".to_string(), |n| n.name.clone());
+                        if entity == *player_entity {
+                            gamelog.entries.push(format!(
+                                "You use {} on {}, who is now {}.",
+                                item_name,
+                                mob_name,
+                                status.kind.name().to_lowercase()
+                            ));
+                        } else {
+                            gamelog.entries.push(format!(
+                                "{} uses {} on {}, who is now {}.",
+                                user_name,
+                                item_name,
+                                mob_name,
+                                status.kind.name().to_lowercase()
+                            ));
+                        }
+                        used_item = true;
                     }
                 }
             }
-            for mob in add_confusion.iter() {
-                confused
-                    .insert(mob.0, Confusion { turns: mob.1 })
-                    .expect("Unable to insert status");
-            }
 
             // If its a consumable, we delete it on use
             if used_item {
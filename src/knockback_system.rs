@@ -0,0 +1,151 @@
+use super::{
+    gamelog::GameLog, particle_system::ParticleBuilder, BlocksTile, DamageType, EntityMoved,
+    Equipped, Knockback, Map, Name, Position, SufferDamage, Viewshed, WeaponStats,
+};
+use rltk::Point;
+use specs::prelude::*;
+
+/// Damage taken from being slammed into a wall or another creature.
+const IMPACT_DAMAGE: i32 = 3;
+
+/// Moves shoved entities one tile at a time, stopping them (painfully) at whatever
+/// they hit first.
+pub struct KnockbackSystem {}
+
+impl<'a> System<'a> for KnockbackSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        WriteExpect<'a, GameLog>,
+        WriteExpect<'a, Map>,
+        WriteExpect<'a, Point>,
+        ReadExpect<'a, Entity>,
+        WriteStorage<'a, Knockback>,
+        WriteStorage<'a, Position>,
+        WriteStorage<'a, Viewshed>,
+        WriteStorage<'a, EntityMoved>,
+        WriteStorage<'a, SufferDamage>,
+        WriteStorage<'a, Equipped>,
+        ReadStorage<'a, WeaponStats>,
+        ReadStorage<'a, BlocksTile>,
+        ReadStorage<'a, Name>,
+        WriteExpect<'a, ParticleBuilder>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            mut log,
+            mut map,
+            mut player_pos,
+            player_entity,
+            mut knockbacks,
+            mut positions,
+            mut viewsheds,
+            mut entity_moved,
+            mut inflict_damage,
+            mut equipped,
+            weapon_stats,
+            blockers,
+            names,
+            mut particle_builder,
+        ) = data;
+
+        let mut landings: Vec<(Entity, Position)> = Vec::new();
+        for (entity, knockback, pos) in (&entities, &knockbacks, &mut positions).join() {
+            let start = (pos.x, pos.y);
+            for _ in 0..knockback.distance {
+                let (x, y) = (pos.x + knockback.dx, pos.y + knockback.dy);
+                if x < 0 || x > map.width - 1 || y < 0 || y > map.height - 1 {
+                    break;
+                }
+                let idx = map.xy_idx(x, y);
+                if map.blocked[idx] {
+                    let obstacle = map.tile_content[idx]
+                        .iter()
+                        .find(|e| blockers.get(**e).is_some())
+                        .and_then(|e| names.get(*e))
+                        .map_or("the wall".to_string(), |n| n.name.clone());
+                    if let Some(name) = names.get(entity) {
+                        log.entries.push(format!(
+                            "{} slams into {}, for {} hp.",
+                            &name.name, obstacle, IMPACT_DAMAGE
+                        ));
+                    }
+                    SufferDamage::new_damage(
+                        &mut inflict_damage,
+                        entity,
+                        IMPACT_DAMAGE,
+                        DamageType::Blunt,
+                    );
+
+                    // Sparks fly back off whatever was hit
+                    for spread in [-1.0, 1.0] {
+                        let (dx, dy) = (knockback.dx as f32, knockback.dy as f32);
+                        particle_builder.drift(
+                            x,
+                            y,
+                            rltk::RGB::named(rltk::YELLOW),
+                            rltk::to_cp437('\''),
+                            (-dx * 6.0 + dy * spread * 4.0, -dy * 6.0 + dx * spread * 4.0),
+                            300.0,
+                        );
+                    }
+                    break;
+                }
+
+                // Keep the map index up to date so later pushes this turn collide properly
+                let old_idx = map.xy_idx(pos.x, pos.y);
+                map.tile_content[old_idx].retain(|e| *e != entity);
+                map.tile_content[idx].push(entity);
+                if blockers.get(entity).is_some() {
+                    map.blocked[old_idx] = false;
+                    map.blocked[idx] = true;
+                }
+                pos.x = x;
+                pos.y = y;
+            }
+
+            if (pos.x, pos.y) == start {
+                continue;
+            }
+            if let Some(viewshed) = viewsheds.get_mut(entity) {
+                viewshed.dirty = true;
+            }
+            entity_moved
+                .insert(entity, EntityMoved {})
+                .expect("Unable to insert marker");
+            if entity == *player_entity {
+                player_pos.x = pos.x;
+                player_pos.y = pos.y;
+            }
+            if weapon_stats.get(entity).is_some() {
+                landings.push((entity, pos.clone()));
+            }
+        }
+
+        // A weapon that lands beyond its tether is knocked out of its owner's grip
+        for (weapon, landed) in landings {
+            let stats = weapon_stats.get(weapon).unwrap();
+            let weapon_name = names
+                .get(weapon)
+                .map_or("the weapon".to_string(), |n| n.name.clone());
+            let from_owner = positions.get(stats.owner).map_or(i32::MAX, |owner_pos| {
+                i32::max(i32::abs(landed.x - owner_pos.x), i32::abs(landed.y - owner_pos.y))
+            });
+            if from_owner > stats.tether() {
+                equipped.remove(stats.item);
+                positions
+                    .insert(stats.item, landed)
+                    .expect("Unable to insert position");
+                log.entries
+                    .push(format!("{} is knocked out of reach!", weapon_name));
+            } else {
+                log.entries
+                    .push(format!("{} is knocked aside.", weapon_name));
+            }
+        }
+
+        knockbacks.clear();
+    }
+}
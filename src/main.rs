@@ -13,6 +13,19 @@ mod rect;
 pub use rect::Rect;
 mod visibility_system;
 use visibility_system::VisibilitySystem;
+mod benchmark;
+mod mapgen_visualiser;
+use mapgen_visualiser::MapGenVisualiser;
+mod flow_map_system;
+use flow_map_system::{FlowMapSystem, FlowMaps};
+mod pack_system;
+use pack_system::{PackPlannerSystem, PackPlans};
+mod noise_system;
+use noise_system::{HearingSystem, Noises};
+mod boss_system;
+use boss_system::BossSystem;
+mod telegraph_system;
+use telegraph_system::TelegraphSystem;
 mod monster_ai_system;
 use monster_ai_system::MonsterAI;
 mod map_indexing_system;
@@ -32,6 +45,20 @@ pub mod random_table;
 pub mod rex_assets;
 pub mod saveload_system;
 mod trigger_system;
+mod weapon_system;
+use weapon_system::WeaponSystem;
+mod stamina_system;
+use stamina_system::StaminaSystem;
+mod dodge_system;
+use dodge_system::DodgeSystem;
+mod knockback_system;
+use knockback_system::KnockbackSystem;
+mod ranged_combat_system;
+use ranged_combat_system::RangedCombatSystem;
+mod corpse_system;
+use corpse_system::CorpseDecaySystem;
+mod status_effect_system;
+use status_effect_system::StatusEffectSystem;
 
 #[derive(PartialEq, Copy, Clone)]
 pub enum RunState {
@@ -52,26 +79,48 @@ pub enum RunState {
     MoveWeapon,
     MoveShield,
     Dodge,
+    Disarm,
 }
 
 pub struct State {
     pub ecs: World,
+    pub mapgen: MapGenVisualiser,
 }
 
 impl State {
     fn run_systems(&mut self) {
         let mut vis = VisibilitySystem {};
         vis.run_now(&self.ecs);
+        let mut flow_maps = FlowMapSystem {};
+        flow_maps.run_now(&self.ecs);
+        let mut packs = PackPlannerSystem {};
+        packs.run_now(&self.ecs);
         let mut mob = MonsterAI {};
         mob.run_now(&self.ecs);
         let mut triggers = trigger_system::TriggerSystem {};
         triggers.run_now(&self.ecs);
         let mut mapindex = MapIndexingSystem {};
         mapindex.run_now(&self.ecs);
+        let mut telegraphs = TelegraphSystem {};
+        telegraphs.run_now(&self.ecs);
+        let mut boss = BossSystem {};
+        boss.run_now(&self.ecs);
         let mut melee = MeleeCombatSystem {};
         melee.run_now(&self.ecs);
+        let mut ranged = RangedCombatSystem {};
+        ranged.run_now(&self.ecs);
+        let mut knockback = KnockbackSystem {};
+        knockback.run_now(&self.ecs);
+        let mut status_effects = StatusEffectSystem {};
+        status_effects.run_now(&self.ecs);
+        let mut corpses = CorpseDecaySystem {};
+        corpses.run_now(&self.ecs);
         let mut damage = DamageSystem {};
         damage.run_now(&self.ecs);
+        let mut stamina = StaminaSystem {};
+        stamina.run_now(&self.ecs);
+        let mut dodges = DodgeSystem {};
+        dodges.run_now(&self.ecs);
         let mut pickup = ItemCollectionSystem {};
         pickup.run_now(&self.ecs);
         let mut itemuse = ItemUseSystem {};
@@ -80,6 +129,10 @@ impl State {
         drop_items.run_now(&self.ecs);
         let mut item_remove = ItemRemoveSystem {};
         item_remove.run_now(&self.ecs);
+        let mut weapons = WeaponSystem {};
+        weapons.run_now(&self.ecs);
+        let mut hearing = HearingSystem {};
+        hearing.run_now(&self.ecs);
         let mut particles = particle_system::ParticleSpawnSystem {};
         particles.run_now(&self.ecs);
 
@@ -89,14 +142,27 @@ impl State {
     fn generate_world_map(&mut self, new_depth: i32) {
         let (player_x, player_y);
         {
-            let mut builder = map_builders::random_builder(new_depth);
-            builder.build_map();
+            let mut builder = {
+                let mut rng = self.ecs.write_resource::<rltk::RandomNumberGenerator>();
+                let mut builder = map_builders::random_builder(new_depth, &mut rng);
+                builder.build_data.recording = self.mapgen.enabled;
+                builder.build_map(&mut rng);
+                builder
+            };
+            if self.mapgen.enabled {
+                self.mapgen
+                    .start(std::mem::take(&mut builder.build_data.history));
+            }
 
             let player_start;
             {
                 let mut worldmap_resource = self.ecs.write_resource::<Map>();
-                *worldmap_resource = builder.get_map();
-                player_start = builder.get_starting_position();
+                *worldmap_resource = builder.build_data.map.clone();
+                player_start = builder
+                    .build_data
+                    .starting_position
+                    .clone()
+                    .expect("The map builders never chose a starting position");
             }
 
             // Spawn map items
@@ -123,7 +189,6 @@ impl State {
             }
         }
 
-        spawner::player_weapon(&mut self.ecs);
         spawner::reset_weapon_locations(&mut self.ecs);
     }
 }
@@ -137,7 +202,11 @@ impl GameState for State {
         }
 
         ctx.cls();
-        particle_system::cull_dead_particles(&mut self.ecs, ctx);
+        if self.mapgen.is_playing() {
+            self.mapgen.tick(ctx);
+            return;
+        }
+        particle_system::update_particles(&mut self.ecs, ctx);
 
         match newrunstate {
             RunState::MainMenu { .. } => {}
@@ -148,11 +217,14 @@ impl GameState for State {
                 {
                     let positions = self.ecs.read_storage::<Position>();
                     let renderables = self.ecs.read_storage::<Renderable>();
+                    let hidden = self.ecs.read_storage::<Hidden>();
                     let map = self.ecs.fetch::<Map>();
 
-                    let mut data = (&positions, &renderables).join().collect::<Vec<_>>();
+                    let mut data = (&positions, &renderables, !&hidden)
+                        .join()
+                        .collect::<Vec<_>>();
                     data.sort_by(|&a, &b| b.1.render_order.cmp(&a.1.render_order));
-                    for (pos, render) in data.iter() {
+                    for (pos, render, _hidden) in data.iter() {
                         let idx = map.xy_idx(pos.x, pos.y);
 
                         if map.visible_tiles[idx] {
@@ -166,6 +238,14 @@ impl GameState for State {
         }
 
         match newrunstate {
+            // Let blocking animations play out before the turn carries on; any key skips them
+            RunState::AwaitingInput | RunState::PlayerTurn | RunState::MonsterTurn
+                if particle_system::animation_playing(&self.ecs) =>
+            {
+                if ctx.key.is_some() {
+                    particle_system::skip_animations(&mut self.ecs);
+                }
+            }
             RunState::PreRun => {
                 self.run_systems();
                 self.ecs.maintain();
@@ -177,12 +257,20 @@ impl GameState for State {
             RunState::PlayerTurn => {
                 self.run_systems();
                 self.ecs.maintain();
-                newrunstate = RunState::MonsterTurn;
+                if status_effect_system::player_gets_extra_action(&mut self.ecs) {
+                    newrunstate = RunState::AwaitingInput;
+                } else {
+                    newrunstate = RunState::MonsterTurn;
+                }
             }
             RunState::MonsterTurn => {
                 self.run_systems();
                 self.ecs.maintain();
-                newrunstate = RunState::AwaitingInput;
+                if status_effect_system::monsters_get_extra_turn(&self.ecs) {
+                    newrunstate = RunState::MonsterTurn;
+                } else {
+                    newrunstate = RunState::AwaitingInput;
+                }
             }
             RunState::ShowInventory => {
                 let result = gui::show_inventory(self, ctx);
@@ -253,13 +341,30 @@ impl GameState for State {
                     gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
                     gui::ItemMenuResult::NoResponse => {}
                     gui::ItemMenuResult::Selected => {
-                        let mut intent = self.ecs.write_storage::<WantsToUseItem>();
-                        intent
-                            .insert(
-                                *self.ecs.fetch::<Entity>(),
-                                WantsToUseItem { item, target: result.1 },
-                            )
-                            .expect("Unable to insert intent");
+                        let target = result.1.unwrap();
+                        let player_entity = *self.ecs.fetch::<Entity>();
+                        if self.ecs.read_storage::<RangedWeapon>().get(item).is_some() {
+                            let mut intent = self.ecs.write_storage::<WantsToShoot>();
+                            intent
+                                .insert(player_entity, WantsToShoot { weapon: item, target })
+                                .expect("Unable to insert intent");
+                        } else {
+                            // Bolts of magic stop at the first thing in the way; blasts don't
+                            let mut target = target;
+                            if self.ecs.read_storage::<AreaOfEffect>().get(item).is_none() {
+                                let path = gui::targeting_path(&self.ecs, target);
+                                if let Some(end) = path.last() {
+                                    target = *end;
+                                }
+                            }
+                            let mut intent = self.ecs.write_storage::<WantsToUseItem>();
+                            intent
+                                .insert(
+                                    player_entity,
+                                    WantsToUseItem { item, target: Some(target) },
+                                )
+                                .expect("Unable to insert intent");
+                        }
                         newrunstate = RunState::PlayerTurn;
                     }
                 }
@@ -335,6 +440,9 @@ impl GameState for State {
                 newrunstate = player_dodge_input(self, ctx);
                 // newrunstate = RunState::PreRun;
             }
+            RunState::Disarm => {
+                newrunstate = player_disarm_input(self, ctx);
+            }
         }
 
         {
@@ -352,6 +460,7 @@ impl State {
         let backpack = self.ecs.read_storage::<InBackpack>();
         let player_entity = self.ecs.fetch::<Entity>();
         let equipped = self.ecs.read_storage::<Equipped>();
+        let weapons = self.ecs.read_storage::<WeaponStats>();
 
         let mut to_delete: Vec<Entity> = Vec::new();
         for entity in entities.join() {
@@ -378,6 +487,14 @@ impl State {
                 }
             }
 
+            // Nor the weapon entity they are wielding
+            let weapon = weapons.get(entity);
+            if let Some(weapon) = weapon {
+                if weapon.owner == *player_entity {
+                    should_delete = false;
+                }
+            }
+
             if should_delete {
                 to_delete.push(entity);
             }
@@ -400,7 +517,7 @@ impl State {
             let worldmap_resource = self.ecs.write_resource::<Map>();
             current_depth = worldmap_resource.depth;
         }
-        if current_depth > 3 {
+        if current_depth >= map_builders::BOSS_DEPTH {
             return false;
         }
 
@@ -451,6 +568,11 @@ impl State {
 rltk::embedded_resource!(FONT, "../resources/Gold Box 8x8 Monospaced_1x.png");
 
 fn main() -> rltk::BError {
+    if std::env::args().any(|arg| arg == "--benchmark") {
+        benchmark::run_pathing_benchmark();
+        return Ok(());
+    }
+
     rltk::link_resource!(FONT, "resources/Gold Box 8x8 Monospaced_1x.png");
 
     use rltk::RltkBuilder;
@@ -461,7 +583,10 @@ fn main() -> rltk::BError {
         .build()?;
     context.with_post_scanlines(true);
 
-    let mut gs = State { ecs: World::new() };
+    let mut gs = State {
+        ecs: World::new(),
+        mapgen: MapGenVisualiser::new(std::env::args().any(|arg| arg == "--mapgen")),
+    };
     gs.ecs.register::<Position>();
     gs.ecs.register::<Renderable>();
     gs.ecs.register::<Player>();
@@ -482,7 +607,24 @@ fn main() -> rltk::BError {
     gs.ecs.register::<WantsToPickupItem>();
     gs.ecs.register::<WantsToUseItem>();
     gs.ecs.register::<WantsToDropItem>();
-    gs.ecs.register::<Confusion>();
+    gs.ecs.register::<StatusEffects>();
+    gs.ecs.register::<InflictsStatus>();
+    gs.ecs.register::<Hidden>();
+    gs.ecs.register::<LootTable>();
+    gs.ecs.register::<Corpse>();
+    gs.ecs.register::<Brain>();
+    gs.ecs.register::<PackMember>();
+    gs.ecs.register::<Allegiance>();
+    gs.ecs.register::<Boss>();
+    gs.ecs.register::<Telegraph>();
+    gs.ecs.register::<WindUpAttack>();
+    gs.ecs.register::<PendingAttack>();
+    gs.ecs.register::<Ammo>();
+    gs.ecs.register::<RangedWeapon>();
+    gs.ecs.register::<WantsToShoot>();
+    gs.ecs.register::<SoundsAlarm>();
+    gs.ecs.register::<Teleports>();
+    gs.ecs.register::<Alerted>();
     gs.ecs.register::<SimpleMarker<SerializeMe>>();
     gs.ecs.register::<SerializationHelper>();
     gs.ecs.register::<Equippable>();
@@ -491,6 +633,17 @@ fn main() -> rltk::BError {
     gs.ecs.register::<DefenseBonus>();
     gs.ecs.register::<WantsToRemoveItem>();
     gs.ecs.register::<WeaponStats>();
+    gs.ecs.register::<Wieldable>();
+    gs.ecs.register::<Broken>();
+    gs.ecs.register::<ProvidesRepair>();
+    gs.ecs.register::<Stamina>();
+    gs.ecs.register::<Exhausted>();
+    gs.ecs.register::<PowerAttack>();
+    gs.ecs.register::<Dodging>();
+    gs.ecs.register::<Knockback>();
+    gs.ecs.register::<Resistant>();
+    gs.ecs.register::<Vulnerable>();
+    gs.ecs.register::<NaturalAttack>();
     gs.ecs.register::<ParticleLifetime>();
     gs.ecs.register::<EntryTrigger>();
     gs.ecs.register::<EntityMoved>();
@@ -499,6 +652,9 @@ fn main() -> rltk::BError {
 
     gs.ecs.insert(SimpleMarkerAllocator::<SerializeMe>::new());
     gs.ecs.insert(particle_system::ParticleBuilder::new());
+    gs.ecs.insert(FlowMaps::default());
+    gs.ecs.insert(PackPlans::default());
+    gs.ecs.insert(Noises::default());
     gs.ecs.insert(rex_assets::RexAssets::new());
 
     gs.ecs.insert(Map::new(1));
@@ -46,11 +46,15 @@ impl Map {
         !self.blocked[idx]
     }
 
+    /// Whether anything could ever walk on a tile, whoever is standing there right now.
+    pub fn is_walkable(&self, idx: usize) -> bool {
+        let tile = self.tiles[idx];
+        tile != TileType::StoneWall && tile != TileType::DirtWall && tile != TileType::DirtWall2
+    }
+
     pub fn populate_blocked(&mut self) {
-        for (i, tile) in self.tiles.iter_mut().enumerate() {
-            self.blocked[i] = *tile == TileType::StoneWall
-                || *tile == TileType::DirtWall
-                || *tile == TileType::DirtWall2;
+        for i in 0..self.tiles.len() {
+            self.blocked[i] = !self.is_walkable(i);
         }
     }
 
@@ -153,7 +157,11 @@ impl Algorithm2D for Map {
 
 pub fn draw_map(ecs: &World, ctx: &mut Rltk) {
     let map = ecs.fetch::<Map>();
+    draw_tiles(&map, ctx);
+}
 
+/// Draws the tiles of any map, not just the one being played on.
+pub fn draw_tiles(map: &Map, ctx: &mut Rltk) {
     let mut y = 0;
     let mut x = 0;
     for (idx, tile) in map.tiles.iter().enumerate() {
@@ -0,0 +1,62 @@
+use super::{BuildData, InitialMapBuilder, Position, TileType};
+use rltk::RandomNumberGenerator;
+
+/// The level the boss waits on. There is no way down from it; the game ends with the
+/// boss's death.
+pub const BOSS_DEPTH: i32 = 4;
+
+/// Half the width and height of the arena.
+const ARENA_RADIUS: (i32, i32) = (30, 17);
+
+/// A single great oval hall with a ring of pillars to hide behind, the player coming in at
+/// the bottom and the boss waiting at the top.
+pub struct BossArenaBuilder {}
+
+impl InitialMapBuilder for BossArenaBuilder {
+    fn build_map(&mut self, _rng: &mut RandomNumberGenerator, build_data: &mut BuildData) {
+        let map = &mut build_data.map;
+        let centre = (map.width / 2, map.height / 2);
+        for y in 1..map.height - 1 {
+            for x in 1..map.width - 1 {
+                let dx = (x - centre.0) as f32 / ARENA_RADIUS.0 as f32;
+                let dy = (y - centre.1) as f32 / ARENA_RADIUS.1 as f32;
+                if dx * dx + dy * dy <= 1.0 {
+                    let idx = map.xy_idx(x, y);
+                    map.tiles[idx] = TileType::Floor;
+                }
+            }
+        }
+
+        // Pillars round the middle, for cover from the boss's attacks
+        for i in 0..8 {
+            let angle = i as f32 * std::f32::consts::PI / 4.0;
+            let x = centre.0 + (angle.cos() * ARENA_RADIUS.0 as f32 * 0.55) as i32;
+            let y = centre.1 + (angle.sin() * ARENA_RADIUS.1 as f32 * 0.55) as i32;
+            for (px, py) in [(x, y), (x + 1, y), (x, y + 1), (x + 1, y + 1)] {
+                let idx = map.xy_idx(px, py);
+                map.tiles[idx] = TileType::StoneWall;
+            }
+        }
+
+        let start = Position { x: centre.0, y: centre.1 + ARENA_RADIUS.1 - 2 };
+        let boss = map.xy_idx(centre.0, centre.1 - ARENA_RADIUS.1 + 4);
+        build_data
+            .spawn_list
+            .push((boss, "Koschei the Deathless".to_string()));
+
+        // Something to patch the player up with on the way in
+        for x in [start.x - 2, start.x + 2] {
+            let idx = map.xy_idx(x, start.y);
+            build_data
+                .spawn_list
+                .push((idx, "Health Potion".to_string()));
+        }
+        build_data.starting_position = Some(start);
+    }
+}
+
+impl BossArenaBuilder {
+    pub fn new() -> Box<BossArenaBuilder> {
+        Box::new(BossArenaBuilder {})
+    }
+}
@@ -0,0 +1,111 @@
+use super::{
+    apply_horizontal_tunnel, apply_room_to_map, apply_vertical_tunnel, BuildData,
+    InitialMapBuilder, Map, Rect,
+};
+use rltk::RandomNumberGenerator;
+
+/// How many times to try placing a room.
+const ATTEMPTS: i32 = 240;
+
+/// The largest a room's sides get.
+const MAX_ROOM_SIZE: i32 = 10;
+
+/// Rooms placed by binary space partition: the map is cut into quarters, and a room is
+/// put somewhere in one of the pieces, which is then cut into quarters itself. Rooms
+/// keep a wall's width apart, and are joined left to right by corridors.
+pub struct BspDungeonBuilder {
+    rects: Vec<Rect>,
+}
+
+impl InitialMapBuilder for BspDungeonBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuildData) {
+        let (w, h) = (build_data.map.width, build_data.map.height);
+        let mut rooms: Vec<Rect> = Vec::new();
+
+        self.rects.clear();
+        self.rects.push(Rect::new(2, 2, w - 5, h - 5));
+        let first = self.rects[0];
+        self.add_subrects(first);
+
+        for _ in 0..ATTEMPTS {
+            let rect = self.rects[rng.range(0, self.rects.len() as i32) as usize];
+            let candidate = random_sub_rect(rect, rng);
+            if is_possible(&build_data.map, &candidate) {
+                apply_room_to_map(&mut build_data.map, &candidate);
+                rooms.push(candidate);
+                self.add_subrects(rect);
+                build_data.take_snapshot();
+            }
+        }
+
+        rooms.sort_by_key(|room| room.x1);
+        for pair in rooms.windows(2) {
+            let (prev_x, prev_y) = pair[0].center();
+            let (new_x, new_y) = pair[1].center();
+            if rng.range(0, 2) == 1 {
+                apply_horizontal_tunnel(&mut build_data.map, prev_x, new_x, prev_y);
+                apply_vertical_tunnel(&mut build_data.map, prev_y, new_y, new_x);
+            } else {
+                apply_vertical_tunnel(&mut build_data.map, prev_y, new_y, prev_x);
+                apply_horizontal_tunnel(&mut build_data.map, prev_x, new_x, new_y);
+            }
+            build_data.take_snapshot();
+        }
+
+        build_data.rooms = Some(rooms);
+    }
+}
+
+impl BspDungeonBuilder {
+    pub fn new() -> Box<BspDungeonBuilder> {
+        Box::new(BspDungeonBuilder { rects: Vec::new() })
+    }
+
+    /// Cuts a rectangle into quarters, for later rooms to go in.
+    fn add_subrects(&mut self, rect: Rect) {
+        let width = i32::abs(rect.x1 - rect.x2);
+        let height = i32::abs(rect.y1 - rect.y2);
+        let half_width = i32::max(width / 2, 1);
+        let half_height = i32::max(height / 2, 1);
+
+        self.rects
+            .push(Rect::new(rect.x1, rect.y1, half_width, half_height));
+        self.rects
+            .push(Rect::new(rect.x1, rect.y1 + half_height, half_width, half_height));
+        self.rects
+            .push(Rect::new(rect.x1 + half_width, rect.y1, half_width, half_height));
+        self.rects.push(Rect::new(
+            rect.x1 + half_width,
+            rect.y1 + half_height,
+            half_width,
+            half_height,
+        ));
+    }
+}
+
+/// A room of random size somewhere inside a rectangle.
+fn random_sub_rect(rect: Rect, rng: &mut RandomNumberGenerator) -> Rect {
+    let rect_width = i32::abs(rect.x1 - rect.x2);
+    let rect_height = i32::abs(rect.y1 - rect.y2);
+
+    let w = i32::max(3, rng.roll_dice(1, i32::min(rect_width, MAX_ROOM_SIZE)));
+    let h = i32::max(3, rng.roll_dice(1, i32::min(rect_height, MAX_ROOM_SIZE)));
+    let x = rect.x1 + rng.roll_dice(1, 6) - 1;
+    let y = rect.y1 + rng.roll_dice(1, 6) - 1;
+    Rect::new(x, y, w, h)
+}
+
+/// Whether a room fits on the map, with solid rock all round it.
+fn is_possible(map: &Map, room: &Rect) -> bool {
+    for y in room.y1 - 2..=room.y2 + 2 {
+        for x in room.x1 - 2..=room.x2 + 2 {
+            if x < 1 || x > map.width - 2 || y < 1 || y > map.height - 2 {
+                return false;
+            }
+            if map.is_walkable(map.xy_idx(x, y)) {
+                return false;
+            }
+        }
+    }
+    true
+}
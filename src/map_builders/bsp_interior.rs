@@ -0,0 +1,117 @@
+use super::{apply_room_to_map, BuildData, InitialMapBuilder, Rect, TileType};
+use rltk::RandomNumberGenerator;
+
+/// The smallest a room's floor gets across.
+const MIN_ROOM_SIZE: i32 = 3;
+
+/// Rooms with floors at least this big across are always split again.
+const MAX_ROOM_SIZE: i32 = 10;
+
+/// The chance, as 1 in this many, of a door between two rooms that are already joined.
+const EXTRA_DOOR_CHANCE: i32 = 10;
+
+/// The inside of a building: the whole map is split in two again and again, and every
+/// piece becomes a room sharing its walls with its neighbours. There are no corridors;
+/// the rooms open straight into each other through doors.
+pub struct BspInteriorBuilder {}
+
+impl InitialMapBuilder for BspInteriorBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuildData) {
+        let (w, h) = (build_data.map.width, build_data.map.height);
+        let mut rooms: Vec<Rect> = Vec::new();
+        subdivide(Rect { x1: 1, y1: 1, x2: w - 3, y2: h - 3 }, rng, &mut rooms);
+
+        for room in rooms.iter() {
+            apply_room_to_map(&mut build_data.map, room);
+            build_data.take_snapshot();
+        }
+
+        // Join each room to one already reachable, until every room is
+        let mut connected = vec![false; rooms.len()];
+        connected[0] = true;
+        let mut joined = 1;
+        while joined < rooms.len() {
+            for i in 0..rooms.len() {
+                for j in 0..rooms.len() {
+                    if !connected[i] || connected[j] {
+                        continue;
+                    }
+                    if let Some((x, y)) = shared_wall(&rooms[i], &rooms[j], rng) {
+                        let idx = build_data.map.xy_idx(x, y);
+                        build_data.map.tiles[idx] = TileType::Door;
+                        connected[j] = true;
+                        joined += 1;
+                    }
+                }
+            }
+        }
+
+        // Then the odd extra door, so there's more than one way round
+        for i in 0..rooms.len() {
+            for j in i + 1..rooms.len() {
+                if rng.roll_dice(1, EXTRA_DOOR_CHANCE) != 1 {
+                    continue;
+                }
+                if let Some((x, y)) = shared_wall(&rooms[i], &rooms[j], rng) {
+                    let idx = build_data.map.xy_idx(x, y);
+                    build_data.map.tiles[idx] = TileType::Door;
+                }
+            }
+        }
+        build_data.take_snapshot();
+
+        build_data.rooms = Some(rooms);
+    }
+}
+
+impl BspInteriorBuilder {
+    pub fn new() -> Box<BspInteriorBuilder> {
+        Box::new(BspInteriorBuilder {})
+    }
+}
+
+/// Splits a rectangle in two across its longer side, and each half again, until the
+/// pieces are small enough to be rooms. Both halves share the wall along the cut.
+fn subdivide(rect: Rect, rng: &mut RandomNumberGenerator, rooms: &mut Vec<Rect>) {
+    let width = rect.x2 - rect.x1;
+    let height = rect.y2 - rect.y1;
+    let can_split_x = width > MIN_ROOM_SIZE * 2;
+    let can_split_y = height > MIN_ROOM_SIZE * 2;
+    let big = width > MAX_ROOM_SIZE || height > MAX_ROOM_SIZE;
+    if !(can_split_x || can_split_y) || (!big && rng.roll_dice(1, 2) == 1) {
+        rooms.push(rect);
+        return;
+    }
+
+    if can_split_x && (width >= height || !can_split_y) {
+        let cut = rect.x1 + rng.range(MIN_ROOM_SIZE + 1, width - MIN_ROOM_SIZE + 1);
+        subdivide(Rect { x2: cut - 1, ..rect }, rng, rooms);
+        subdivide(Rect { x1: cut, ..rect }, rng, rooms);
+    } else {
+        let cut = rect.y1 + rng.range(MIN_ROOM_SIZE + 1, height - MIN_ROOM_SIZE + 1);
+        subdivide(Rect { y2: cut - 1, ..rect }, rng, rooms);
+        subdivide(Rect { y1: cut, ..rect }, rng, rooms);
+    }
+}
+
+/// A spot for a door in the wall two rooms share, if they share one.
+fn shared_wall(a: &Rect, b: &Rect, rng: &mut RandomNumberGenerator) -> Option<(i32, i32)> {
+    // Rooms own the wall on their top and left, and the next room's on their bottom and
+    // right, so neighbours share a line of wall where one's far wall is the other's near
+    let mut along = |from: i32, to: i32| {
+        if from <= to {
+            Some(rng.range(from, to + 1))
+        } else {
+            None
+        }
+    };
+    if a.x2 + 1 == b.x1 || b.x2 + 1 == a.x1 {
+        let x = if a.x2 + 1 == b.x1 { b.x1 } else { a.x1 };
+        along(i32::max(a.y1, b.y1) + 1, i32::min(a.y2, b.y2)).map(|y| (x, y))
+    } else if a.y2 + 1 == b.y1 || b.y2 + 1 == a.y1 {
+        let y = if a.y2 + 1 == b.y1 { b.y1 } else { a.y1 };
+        along(i32::max(a.x1, b.x1) + 1, i32::min(a.x2, b.x2)).map(|x| (x, y))
+    } else {
+        None
+    }
+}
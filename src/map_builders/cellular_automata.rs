@@ -0,0 +1,59 @@
+use super::{BuildData, InitialMapBuilder, TileType};
+use rltk::RandomNumberGenerator;
+
+/// The chance, out of 100, that a tile starts out open.
+const OPEN_CHANCE: i32 = 54;
+
+/// How many rounds the automaton is run for.
+const ITERATIONS: i32 = 5;
+
+/// Organic caverns grown by a cellular automaton: the map starts out as random noise, and
+/// each round a tile becomes rock if most of its neighbours are rock, and stays rock if
+/// at least half of them are. The rock keeps the texture of the map it was grown on.
+pub struct CellularAutomataBuilder {}
+
+impl InitialMapBuilder for CellularAutomataBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuildData) {
+        let rock = build_data.map.tiles.clone();
+        let (w, h) = (build_data.map.width, build_data.map.height);
+
+        for y in 1..h - 1 {
+            for x in 1..w - 1 {
+                if rng.roll_dice(1, 100) <= OPEN_CHANCE {
+                    let idx = build_data.map.xy_idx(x, y);
+                    build_data.map.tiles[idx] = TileType::Floor;
+                }
+            }
+        }
+        build_data.take_snapshot();
+
+        for _ in 0..ITERATIONS {
+            let map = &build_data.map;
+            let mut tiles = map.tiles.clone();
+            for y in 1..h - 1 {
+                for x in 1..w - 1 {
+                    let mut neighbours = 0;
+                    for dy in -1..=1 {
+                        for dx in -1..=1 {
+                            let idx = map.xy_idx(x + dx, y + dy);
+                            if (dx != 0 || dy != 0) && !map.is_walkable(idx) {
+                                neighbours += 1;
+                            }
+                        }
+                    }
+                    let idx = map.xy_idx(x, y);
+                    let rocky = neighbours >= 5 || (neighbours >= 4 && !map.is_walkable(idx));
+                    tiles[idx] = if rocky { rock[idx] } else { TileType::Floor };
+                }
+            }
+            build_data.map.tiles = tiles;
+            build_data.take_snapshot();
+        }
+    }
+}
+
+impl CellularAutomataBuilder {
+    pub fn new() -> Box<CellularAutomataBuilder> {
+        Box::new(CellularAutomataBuilder {})
+    }
+}
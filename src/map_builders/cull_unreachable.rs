@@ -0,0 +1,28 @@
+use super::{BuildData, FlowMap, MetaMapBuilder, TileType};
+use rltk::RandomNumberGenerator;
+
+/// Fills in every open tile that can't be walked to from the starting position, so
+/// nothing gets spawned where the player could never reach it.
+pub struct CullUnreachable {}
+
+impl MetaMapBuilder for CullUnreachable {
+    fn build_map(&mut self, _rng: &mut RandomNumberGenerator, build_data: &mut BuildData) {
+        let start = build_data
+            .starting_position
+            .as_ref()
+            .expect("Culling needs a starting position");
+        let map = &mut build_data.map;
+        let reachable = FlowMap::build(map, &[(map.xy_idx(start.x, start.y), 0)]);
+        for idx in 0..map.tiles.len() {
+            if map.is_walkable(idx) && reachable.distance(idx).is_none() {
+                map.tiles[idx] = TileType::DirtWall;
+            }
+        }
+    }
+}
+
+impl CullUnreachable {
+    pub fn new() -> Box<CullUnreachable> {
+        Box::new(CullUnreachable {})
+    }
+}
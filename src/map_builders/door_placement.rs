@@ -0,0 +1,48 @@
+use super::{BuildData, Map, MetaMapBuilder, TileType};
+use rltk::RandomNumberGenerator;
+
+/// Hangs a door in every gap in a room's walls that is only one tile wide.
+pub struct DoorPlacement {}
+
+impl MetaMapBuilder for DoorPlacement {
+    fn build_map(&mut self, _rng: &mut RandomNumberGenerator, build_data: &mut BuildData) {
+        let rooms = match &build_data.rooms {
+            None => return,
+            Some(rooms) => rooms.clone(),
+        };
+        let map = &mut build_data.map;
+        for room in rooms.iter() {
+            for y in room.y1..=room.y2 + 1 {
+                for x in room.x1..=room.x2 + 1 {
+                    let on_wall =
+                        x == room.x1 || x == room.x2 + 1 || y == room.y1 || y == room.y2 + 1;
+                    if on_wall && is_doorway(map, x, y) {
+                        let idx = map.xy_idx(x, y);
+                        map.tiles[idx] = TileType::Door;
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl DoorPlacement {
+    pub fn new() -> Box<DoorPlacement> {
+        Box::new(DoorPlacement {})
+    }
+}
+
+/// Whether a tile is open floor squeezed between two walls, either side to side or above
+/// and below.
+fn is_doorway(map: &Map, x: i32, y: i32) -> bool {
+    if x < 1 || x > map.width - 2 || y < 1 || y > map.height - 2 {
+        return false;
+    }
+    let idx = map.xy_idx(x, y);
+    if map.tiles[idx] != TileType::Floor {
+        return false;
+    }
+    let wall = |x: i32, y: i32| !map.is_walkable(map.xy_idx(x, y));
+    (wall(x - 1, y) && wall(x + 1, y) && !wall(x, y - 1) && !wall(x, y + 1))
+        || (wall(x, y - 1) && wall(x, y + 1) && !wall(x - 1, y) && !wall(x + 1, y))
+}
@@ -1,28 +1,183 @@
-use super::{spawner, Map, Position, Rect, TileType};
+use super::{flow_map_system::FlowMap, spawner, Map, Position, Rect, TileType};
 use rltk::RandomNumberGenerator;
 mod simple_map;
 use simple_map::SimpleMapBuilder;
-mod dig_map;
-use dig_map::DigMapBuilder;
-mod dig_map_turb;
-use dig_map_turb::DigMapTurbBuilder;
+mod room_growth;
+use room_growth::RoomGrowthBuilder;
+mod noise_dig;
+use noise_dig::NoiseDigBuilder;
+mod boss_arena;
+use boss_arena::BossArenaBuilder;
+pub use boss_arena::BOSS_DEPTH;
+mod bsp_dungeon;
+use bsp_dungeon::BspDungeonBuilder;
+mod bsp_interior;
+use bsp_interior::BspInteriorBuilder;
+mod cellular_automata;
+use cellular_automata::CellularAutomataBuilder;
+mod door_placement;
+use door_placement::DoorPlacement;
+mod cull_unreachable;
+use cull_unreachable::CullUnreachable;
+mod starting_points;
+use starting_points::{AreaStartingPosition, RoomBasedStartingPosition};
+mod stairs;
+use stairs::{DistantStairs, RoomBasedStairs};
+mod spawns;
+use spawns::{RoomBasedSpawner, ScatteredSpawner};
 mod common;
 use common::*;
 use specs::prelude::*;
 
-pub trait MapBuilder {
-    fn build_map(&mut self);
-    fn spawn_entities(&mut self, ecs: &mut World);
-    fn get_map(&self) -> Map;
-    fn get_starting_position(&self) -> Position;
+/// Everything a level is built up from, passed down the chain for each builder to add to.
+pub struct BuildData {
+    pub map: Map,
+    pub starting_position: Option<Position>,
+    pub rooms: Option<Vec<Rect>>,
+    /// What to spawn once the level is finished, and the index of the tile to put it on
+    pub spawn_list: Vec<(usize, String)>,
+    /// Whether to keep snapshots of the map as it is built, for the visualiser
+    pub recording: bool,
+    pub history: Vec<Map>,
 }
 
-pub fn random_builder(new_depth: i32) -> Box<dyn MapBuilder> {
-    let mut rng = RandomNumberGenerator::new();
+impl BuildData {
+    /// Records how the map looks right now, if the build is being recorded.
+    pub fn take_snapshot(&mut self) {
+        if self.recording {
+            let map = self.map.clone();
+            self.take_snapshot_of(map);
+        }
+    }
+
+    /// Records a map other than the one being built, such as one with rooms drawn on that
+    /// haven't been dug yet.
+    pub fn take_snapshot_of(&mut self, mut map: Map) {
+        if self.recording {
+            map.revealed_tiles.iter_mut().for_each(|tile| *tile = true);
+            map.visible_tiles.iter_mut().for_each(|tile| *tile = true);
+            self.history.push(map);
+        }
+    }
+}
+
+/// Lays down the first version of a level, on a map that is solid rock.
+pub trait InitialMapBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuildData);
+}
+
+/// Works over a level that another builder has already started.
+pub trait MetaMapBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuildData);
+}
+
+/// A level's builders, in the order they run: one initial builder, then any number of
+/// meta builders that each change what the ones before them made.
+pub struct BuilderChain {
+    starter: Option<Box<dyn InitialMapBuilder>>,
+    builders: Vec<Box<dyn MetaMapBuilder>>,
+    pub build_data: BuildData,
+}
+
+impl BuilderChain {
+    pub fn new(new_depth: i32) -> BuilderChain {
+        BuilderChain {
+            starter: None,
+            builders: Vec::new(),
+            build_data: BuildData {
+                map: Map::new(new_depth),
+                starting_position: None,
+                rooms: None,
+                spawn_list: Vec::new(),
+                recording: false,
+                history: Vec::new(),
+            },
+        }
+    }
+
+    pub fn start_with(&mut self, starter: Box<dyn InitialMapBuilder>) {
+        match self.starter {
+            None => self.starter = Some(starter),
+            Some(_) => panic!("You can only have one starting builder."),
+        };
+    }
+
+    pub fn with(&mut self, metabuilder: Box<dyn MetaMapBuilder>) {
+        self.builders.push(metabuilder);
+    }
+
+    pub fn build_map(&mut self, rng: &mut RandomNumberGenerator) {
+        match &mut self.starter {
+            None => panic!("Cannot run a map builder chain without a starting build system"),
+            Some(starter) => starter.build_map(rng, &mut self.build_data),
+        }
+        self.build_data.take_snapshot();
+        for metabuilder in self.builders.iter_mut() {
+            metabuilder.build_map(rng, &mut self.build_data);
+            self.build_data.take_snapshot();
+        }
+    }
+
+    pub fn spawn_entities(&mut self, ecs: &mut World) {
+        let width = self.build_data.map.width as usize;
+        for (idx, name) in self.build_data.spawn_list.iter() {
+            spawner::spawn_item(ecs, (idx % width) as i32, (idx / width) as i32, name);
+        }
+    }
+}
+
+/// Puts together the builders for a new level. The boss always waits on the same level;
+/// above it are caves, dug out or grown, or rooms partitioned out of the rock or out of a
+/// building, and the deeper you go the more likely the caves are to have broken into rooms.
+pub fn random_builder(new_depth: i32, rng: &mut RandomNumberGenerator) -> BuilderChain {
+    let mut builder = BuilderChain::new(new_depth);
+    if new_depth == BOSS_DEPTH {
+        builder.start_with(BossArenaBuilder::new());
+        return builder;
+    }
 
-    match rng.range(0, 1) {
-        4 => Box::new(SimpleMapBuilder::new(new_depth)),
-        5 => Box::new(DigMapBuilder::new(new_depth)),
-        _ => Box::new(DigMapTurbBuilder::new(new_depth)),
+    match rng.roll_dice(1, 6 + new_depth) {
+        1 => {
+            builder.start_with(NoiseDigBuilder::new());
+            builder.with(CullUnreachable::new());
+            builder.with(DistantStairs::new());
+            builder.with(ScatteredSpawner::new());
+        }
+        2 => {
+            builder.start_with(SimpleMapBuilder::new());
+            builder.with(RoomBasedStartingPosition::new());
+            builder.with(DoorPlacement::new());
+            builder.with(RoomBasedStairs::new());
+            builder.with(RoomBasedSpawner::new());
+        }
+        3 => {
+            builder.start_with(CellularAutomataBuilder::new());
+            builder.with(AreaStartingPosition::new());
+            builder.with(CullUnreachable::new());
+            builder.with(DistantStairs::new());
+            builder.with(ScatteredSpawner::new());
+        }
+        4 => {
+            builder.start_with(BspDungeonBuilder::new());
+            builder.with(RoomBasedStartingPosition::new());
+            builder.with(DoorPlacement::new());
+            builder.with(RoomBasedStairs::new());
+            builder.with(RoomBasedSpawner::new());
+        }
+        5 => {
+            builder.start_with(BspInteriorBuilder::new());
+            builder.with(RoomBasedStartingPosition::new());
+            builder.with(DistantStairs::new());
+            builder.with(RoomBasedSpawner::new());
+        }
+        _ => {
+            builder.start_with(RoomGrowthBuilder::new());
+            builder.with(NoiseDigBuilder::turbulent());
+            builder.with(CullUnreachable::new());
+            builder.with(DoorPlacement::new());
+            builder.with(DistantStairs::new());
+            builder.with(ScatteredSpawner::new());
+        }
     }
+    builder
 }
@@ -0,0 +1,244 @@
+use super::{apply_point, BuildData, InitialMapBuilder, MetaMapBuilder, Position, Rect, TileType};
+use noise::{Fbm, NoiseFn, Seedable};
+use rltk::RandomNumberGenerator;
+use std::cmp::{min, Ordering};
+use std::collections::{BinaryHeap, HashSet};
+
+/// Digs a cave out from a point near the middle of the map, always taking the cheapest
+/// tile next to what has been dug so far, with noise deciding what is cheap. With
+/// `turbulent` set the noise is warped by a second layer of noise, which gives twistier
+/// passages.
+///
+/// Started on a map that already has rooms laid out, it is drawn into their interiors,
+/// breaks through at most once on each side, and builds the walls of every room it gets
+/// into. Rooms it never reached are forgotten.
+pub struct NoiseDigBuilder {
+    turbulent: bool,
+}
+
+impl InitialMapBuilder for NoiseDigBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuildData) {
+        self.dig(rng, build_data);
+    }
+}
+
+impl MetaMapBuilder for NoiseDigBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuildData) {
+        self.dig(rng, build_data);
+    }
+}
+
+/// How many tiles to dig between snapshots, when the build is being recorded.
+const DUG_PER_SNAPSHOT: i32 = 20;
+
+/* Struct required for priority queue */
+#[derive(Copy, Clone, PartialEq)]
+struct Location {
+    score: f64,
+    x: i32,
+    y: i32,
+}
+
+impl Eq for Location {}
+
+impl Ord for Location {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .score
+            .partial_cmp(&self.score)
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for Location {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn distance_to_centre(x: i32, y: i32, w: i32, h: i32) -> f64 {
+    ((x - w / 2) as f64 / w as f64).powf(2.0) + ((y - h / 2) as f64 / h as f64).powf(2.0)
+}
+
+/// Renders a list of rooms to an array so we can quickly test if we have hit a room,
+/// and which room we have hit.
+///
+/// We tag room interiors as -2, and corners as -1
+/// normal space is 0, and walls get their own positive index based on the room_id
+/// (should probably use an enum or something)
+fn room_map(rooms: &[Rect], w: i32, h: i32) -> Vec<Vec<i32>> {
+    let mut result = vec![vec![0i32; h as usize]; w as usize];
+
+    for (i, room) in rooms.iter().enumerate() {
+        let room_id = i as i32;
+        for x in room.x1..min(w, room.x2 + 2) {
+            for y in room.y1..min(h, room.y2 + 2) {
+                if x == room.x1 && (y == room.y1 || y == room.y2 + 1) {
+                    result[x as usize][y as usize] = -1;
+                } else if x == room.x2 + 1 && (y == room.y1 || y == room.y2 + 1) {
+                    result[x as usize][y as usize] = -1;
+                } else if x == room.x1 && x == 0 {
+                    // no doors to edge of the map
+                    result[x as usize][y as usize] = -1;
+                } else if y == room.y1 && y == 0 {
+                    result[x as usize][y as usize] = -1;
+                } else if x == room.x1 {
+                    result[x as usize][y as usize] = room_id * 4 + 1;
+                } else if x == room.x2 + 1 {
+                    result[x as usize][y as usize] = room_id * 4 + 2;
+                } else if y == room.y1 {
+                    result[x as usize][y as usize] = room_id * 4 + 3;
+                } else if y == room.y2 + 1 {
+                    result[x as usize][y as usize] = room_id * 4 + 4;
+                } else {
+                    result[x as usize][y as usize] = -2;
+                }
+            }
+        }
+    }
+
+    result
+}
+
+impl NoiseDigBuilder {
+    pub fn new() -> Box<NoiseDigBuilder> {
+        Box::new(NoiseDigBuilder { turbulent: false })
+    }
+
+    pub fn turbulent() -> Box<NoiseDigBuilder> {
+        Box::new(NoiseDigBuilder { turbulent: true })
+    }
+
+    fn dig(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuildData) {
+        const CONSTANT: f64 = -0.1;
+        const EDGE_WEIGHT: f64 = 2.5;
+
+        let (w, h) = (build_data.map.width, build_data.map.height);
+        let iterations = (w * h) / 2;
+
+        let rooms = build_data.rooms.clone().unwrap_or_default();
+        let room_array = room_map(&rooms, w, h);
+        let mut walls_dug = HashSet::<i32>::new();
+
+        let simplex = Fbm::new().set_seed(rng.range(0, u32::MAX) as u32);
+        let simplex2 = Fbm::new().set_seed(rng.range(0, u32::MAX) as u32);
+        let turbulent = self.turbulent;
+        let noise = |x, y| {
+            if !turbulent {
+                return simplex.get([x, y]);
+            }
+            let value = 8.0 * simplex.get([x, y]);
+
+            simplex2.get([x + 4.0 * f64::sin(value), y + 4.0 * f64::cos(value)])
+        };
+
+        let mut heap = BinaryHeap::new();
+
+        // Room corners can never be dug, so they are no good to start from
+        let (start_x, start_y) = loop {
+            let (x, y) = (rng.range(0, w / 2) + w / 4, rng.range(0, h / 2) + h / 4);
+            if room_array[x as usize][y as usize] != -1 {
+                break (x, y);
+            }
+        };
+
+        heap.push(Location { score: 0.0, x: start_x, y: start_y });
+
+        let mut visited = vec![vec![0u8; h as usize]; w as usize];
+
+        // constants for scaling the noise
+        let (dx, dy) = (8.0 / w as f64, 8.0 / h as f64);
+
+        let mut dug = 0;
+        for _ in 0..iterations {
+            if let Some(Location { score, x, y }) = heap.pop() {
+                let mut score = score;
+                if visited[x as usize][y as usize] == 1 {
+                    continue;
+                }
+
+                let room_id = room_array[x as usize][y as usize];
+                if room_id == -1 {
+                    continue;
+                } else if room_id == -2 {
+                    score -= 2.0; // Make room interiors much more likely to be carved out
+                } else if room_id > 0 {
+                    if walls_dug.contains(&room_id) {
+                        continue;
+                    }
+                    walls_dug.insert(room_id);
+                }
+
+                visited[x as usize][y as usize] = 1;
+
+                let (fx, fy) = (x as f64 * dx, y as f64 * dy);
+                let edge = EDGE_WEIGHT * distance_to_centre(x, y, w, h);
+
+                if x > 0 && visited[x as usize - 1][y as usize] == 0 {
+                    heap.push(Location {
+                        score: score + (noise(fx - dx, fy) + CONSTANT) + edge,
+                        x: x - 1,
+                        y,
+                    });
+                }
+
+                if x < w - 1 && visited[x as usize + 1][y as usize] == 0 {
+                    heap.push(Location {
+                        score: score + (noise(fx + dx, fy) + CONSTANT) + edge,
+                        x: x + 1,
+                        y,
+                    });
+                }
+
+                if y > 0 && visited[x as usize][y as usize - 1] == 0 {
+                    heap.push(Location {
+                        score: score + (noise(fx, fy - dy) + CONSTANT) + edge,
+                        x,
+                        y: y - 1,
+                    });
+                }
+
+                if y < h - 1 && visited[x as usize][y as usize + 1] == 0 {
+                    heap.push(Location {
+                        score: score + (noise(fx, fy + dy) + CONSTANT) + edge,
+                        x,
+                        y: y + 1,
+                    });
+                }
+
+                apply_point(&mut build_data.map, x, y, TileType::Floor);
+                dug += 1;
+                if dug % DUG_PER_SNAPSHOT == 0 {
+                    build_data.take_snapshot();
+                }
+            }
+        }
+
+        // Finish off the rooms the digging broke into
+        let map = &mut build_data.map;
+        let mut entered: Vec<usize> = walls_dug.iter().map(|i| ((i - 1) / 4) as usize).collect();
+        entered.sort_unstable();
+        entered.dedup();
+        for room_id in entered.iter() {
+            let room = rooms[*room_id];
+
+            for x in room.x1..min(w, room.x2 + 2) {
+                for y in room.y1..min(h, room.y2 + 2) {
+                    let idx = map.xy_idx(x, y);
+                    if !(map.tiles[idx] == TileType::Door || map.tiles[idx] == TileType::Floor) {
+                        if x == room.x1 || x == room.x2 + 1 || y == room.y1 || y == room.y2 + 1 {
+                            map.tiles[idx] = TileType::StoneWall;
+                        } else {
+                            map.tiles[idx] = TileType::Floor;
+                        }
+                    }
+                }
+            }
+        }
+        if build_data.rooms.is_some() {
+            build_data.rooms = Some(entered.iter().map(|room_id| rooms[*room_id]).collect());
+        }
+
+        build_data.starting_position = Some(Position { x: start_x, y: start_y });
+    }
+}
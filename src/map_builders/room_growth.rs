@@ -0,0 +1,138 @@
+use super::{apply_room_to_map, BuildData, InitialMapBuilder, Position, Rect};
+use rltk::RandomNumberGenerator;
+
+/// How many rooms to try to fit on the map.
+const ROOMS: i32 = 10;
+
+/// The chance each side of a room has of moving out by a tile, every round of growth.
+const GROWTH_CHANCE: f64 = 0.8;
+
+/// How many rounds of growth the rooms get.
+const GROWTH_ROUNDS: i32 = 10;
+
+/// Lays out rooms by scattering seeds across the map and growing them outwards until they
+/// would bump into each other. Nothing is dug: the rooms are only recorded, for a later
+/// builder to carve out.
+pub struct RoomGrowthBuilder {}
+
+impl InitialMapBuilder for RoomGrowthBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuildData) {
+        let (w, h) = (build_data.map.width, build_data.map.height);
+        let seeds = poisson_sample_points(rng, w, h, ROOMS);
+        let rooms = grow_rooms(rng, build_data, w, h, seeds, GROWTH_CHANCE, GROWTH_ROUNDS);
+        build_data.rooms = Some(rooms);
+    }
+}
+
+impl RoomGrowthBuilder {
+    pub fn new() -> Box<RoomGrowthBuilder> {
+        Box::new(RoomGrowthBuilder {})
+    }
+}
+
+/// Use something approaching poisson sampling to find centre points
+/// to grow rooms
+fn poisson_sample_points(
+    rng: &mut RandomNumberGenerator,
+    w: i32,
+    h: i32,
+    count: i32,
+) -> Vec<Position> {
+    let mut result = Vec::new();
+    let mut candidates = Vec::new();
+    for _ in 0..count * 3 {
+        candidates.push(Position { x: rng.range(0, w), y: rng.range(0, h) })
+    }
+
+    let first = candidates.pop().unwrap();
+
+    let mut distances = Vec::new();
+    for candidate in &candidates {
+        distances.push(first.square_distance(candidate));
+    }
+
+    result.push(first);
+
+    for _ in 0..count {
+        let pick = (0..distances.len()).min_by_key(|i| &distances[*i]).unwrap();
+
+        let new = candidates.remove(pick);
+        distances.remove(pick);
+
+        for (i, candidate) in candidates.iter().enumerate() {
+            let distance = new.square_distance(candidate);
+            if distance < distances[i] {
+                distances[i] = distance;
+            }
+        }
+
+        result.push(new);
+    }
+
+    result
+}
+
+fn in_bounds(room: &Rect, w: i32, h: i32) -> bool {
+    room.x1 >= 0 && room.y1 >= 0 && room.x2 < w && room.y2 < h
+}
+
+/// Given a list of positions in the map, grow the rooms outwards
+fn grow_rooms(
+    rng: &mut RandomNumberGenerator,
+    build_data: &mut BuildData,
+    w: i32,
+    h: i32,
+    seeds: Vec<Position>,
+    probability: f64,
+    iterations: i32,
+) -> Vec<Rect> {
+    let mut rooms: Vec<Rect> = seeds
+        .iter()
+        .map(|position| Rect {
+            x1: position.x,
+            y1: position.y,
+            x2: position.x,
+            y2: position.y,
+        })
+        .collect();
+
+    for _ in 0..iterations {
+        for i in 0..rooms.len() {
+            // Try each side in turn, giving up on the room for this round at the map's edge
+            for side in 0..4 {
+                if rng.range(0.0, 1.0) >= probability {
+                    continue;
+                }
+                let mut grown = rooms[i];
+                match side {
+                    0 => grown.x1 -= 1,
+                    1 => grown.y1 -= 1,
+                    2 => grown.x2 += 1,
+                    _ => grown.y2 += 1,
+                }
+
+                if !in_bounds(&grown, w, h) {
+                    break;
+                }
+                let overlaps = rooms
+                    .iter()
+                    .enumerate()
+                    .any(|(j, other)| i != j && grown.intersect(other));
+                if !overlaps {
+                    rooms[i] = grown;
+                }
+            }
+        }
+
+        // The rooms aren't dug yet, so draw them onto a copy to show how they're growing
+        if build_data.recording {
+            let mut snapshot = build_data.map.clone();
+            for room in rooms.iter() {
+                apply_room_to_map(&mut snapshot, room);
+            }
+            build_data.take_snapshot_of(snapshot);
+        }
+    }
+
+    rooms
+}
@@ -1,90 +1,62 @@
 use super::{
-    apply_horizontal_tunnel, apply_room_to_map, apply_vertical_tunnel, spawner, Map, MapBuilder,
-    Position, Rect, TileType,
+    apply_horizontal_tunnel, apply_room_to_map, apply_vertical_tunnel, BuildData,
+    InitialMapBuilder, Rect,
 };
 use rltk::RandomNumberGenerator;
-use specs::prelude::*;
 
-pub struct SimpleMapBuilder {
-    map: Map,
-    starting_position: Position,
-    depth: i32,
-    rooms: Vec<Rect>,
-}
-
-impl MapBuilder for SimpleMapBuilder {
-    fn get_map(&self) -> Map {
-        self.map.clone()
-    }
+/// Rectangular rooms dropped wherever they fit, each joined to the one before it by an
+/// L-shaped corridor.
+pub struct SimpleMapBuilder {}
 
-    fn get_starting_position(&self) -> Position {
-        self.starting_position.clone()
-    }
-
-    fn build_map(&mut self) {
-        self.rooms_and_corridors();
-    }
-
-    fn spawn_entities(&mut self, ecs: &mut World) {
-        for room in self.rooms.iter().skip(1) {
-            spawner::spawn_room(ecs, room, self.depth);
-        }
+impl InitialMapBuilder for SimpleMapBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuildData) {
+        self.rooms_and_corridors(rng, build_data);
     }
 }
 
 impl SimpleMapBuilder {
-    pub fn new(new_depth: i32) -> SimpleMapBuilder {
-        SimpleMapBuilder {
-            map: Map::new(new_depth),
-            starting_position: Position { x: 0, y: 0 },
-            depth: new_depth,
-            rooms: Vec::new(),
-        }
+    pub fn new() -> Box<SimpleMapBuilder> {
+        Box::new(SimpleMapBuilder {})
     }
 
-    fn rooms_and_corridors(&mut self) {
+    fn rooms_and_corridors(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuildData) {
         const MAX_ROOMS: i32 = 30;
         const MIN_SIZE: i32 = 6;
         const MAX_SIZE: i32 = 10;
 
-        let mut rng = RandomNumberGenerator::new();
-
+        let mut rooms: Vec<Rect> = Vec::new();
         for _i in 0..MAX_ROOMS {
             let w = rng.range(MIN_SIZE, MAX_SIZE);
             let h = rng.range(MIN_SIZE, MAX_SIZE);
-            let x = rng.roll_dice(1, self.map.width - w - 1) - 1;
-            let y = rng.roll_dice(1, self.map.height - h - 1) - 1;
+            let x = rng.roll_dice(1, build_data.map.width - w - 1) - 1;
+            let y = rng.roll_dice(1, build_data.map.height - h - 1) - 1;
             let new_room = Rect::new(x, y, w, h);
             let mut ok = true;
-            for other_room in self.rooms.iter() {
+            for other_room in rooms.iter() {
                 if new_room.intersect(other_room) {
                     ok = false
                 }
             }
             if ok {
-                apply_room_to_map(&mut self.map, &new_room);
+                apply_room_to_map(&mut build_data.map, &new_room);
 
-                if !self.rooms.is_empty() {
+                if !rooms.is_empty() {
                     let (new_x, new_y) = new_room.center();
-                    let (prev_x, prev_y) = self.rooms[self.rooms.len() - 1].center();
+                    let (prev_x, prev_y) = rooms[rooms.len() - 1].center();
                     if rng.range(0, 2) == 1 {
-                        apply_horizontal_tunnel(&mut self.map, prev_x, new_x, prev_y);
-                        apply_vertical_tunnel(&mut self.map, prev_y, new_y, new_x);
+                        apply_horizontal_tunnel(&mut build_data.map, prev_x, new_x, prev_y);
+                        apply_vertical_tunnel(&mut build_data.map, prev_y, new_y, new_x);
                     } else {
-                        apply_vertical_tunnel(&mut self.map, prev_y, new_y, prev_x);
-                        apply_horizontal_tunnel(&mut self.map, prev_x, new_x, new_y);
+                        apply_vertical_tunnel(&mut build_data.map, prev_y, new_y, prev_x);
+                        apply_horizontal_tunnel(&mut build_data.map, prev_x, new_x, new_y);
                     }
                 }
 
-                self.rooms.push(new_room);
+                rooms.push(new_room);
+                build_data.take_snapshot();
             }
         }
 
-        let stairs_position = self.rooms[self.rooms.len() - 1].center();
-        let stairs_idx = self.map.xy_idx(stairs_position.0, stairs_position.1);
-        self.map.tiles[stairs_idx] = TileType::DownStairs;
-
-        let start_pos = self.rooms[0].center();
-        self.starting_position = Position { x: start_pos.0, y: start_pos.1 };
+        build_data.rooms = Some(rooms);
     }
 }
@@ -0,0 +1,64 @@
+use super::{spawner, BuildData, FlowMap, MetaMapBuilder, Position, TileType};
+use rltk::RandomNumberGenerator;
+
+/// Walking out from the start, how many floor tiles to pass between one spawn and the
+/// next.
+const SPAWN_SPACING: usize = 40;
+
+/// Fills every room but the first, where the player starts.
+pub struct RoomBasedSpawner {}
+
+impl MetaMapBuilder for RoomBasedSpawner {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuildData) {
+        let rooms = build_data
+            .rooms
+            .as_ref()
+            .expect("Room based spawning needs rooms");
+        for room in rooms.iter().skip(1) {
+            spawner::spawn_room(rng, room, build_data.map.depth, &mut build_data.spawn_list);
+        }
+    }
+}
+
+impl RoomBasedSpawner {
+    pub fn new() -> Box<RoomBasedSpawner> {
+        Box::new(RoomBasedSpawner {})
+    }
+}
+
+/// Spreads spawns evenly through the level, one for every so many floor tiles on the way
+/// out from the starting position.
+pub struct ScatteredSpawner {}
+
+impl MetaMapBuilder for ScatteredSpawner {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuildData) {
+        let start = build_data
+            .starting_position
+            .as_ref()
+            .expect("Scattered spawning needs a starting position");
+        let map = &build_data.map;
+        let distances = FlowMap::build(map, &[(map.xy_idx(start.x, start.y), 0)]);
+        let mut floor: Vec<(i32, usize)> = (0..map.tiles.len())
+            .filter(|idx| map.tiles[*idx] == TileType::Floor)
+            .filter_map(|idx| distances.distance(idx).map(|distance| (distance, idx)))
+            .collect();
+        floor.sort_unstable();
+
+        let positions: Vec<Position> = floor
+            .iter()
+            .skip(SPAWN_SPACING - 1)
+            .step_by(SPAWN_SPACING)
+            .map(|(_distance, idx)| Position {
+                x: *idx as i32 % map.width,
+                y: *idx as i32 / map.width,
+            })
+            .collect();
+        spawner::spawn_locations(rng, &positions, map.depth, &mut build_data.spawn_list);
+    }
+}
+
+impl ScatteredSpawner {
+    pub fn new() -> Box<ScatteredSpawner> {
+        Box::new(ScatteredSpawner {})
+    }
+}
@@ -0,0 +1,50 @@
+use super::{BuildData, FlowMap, MetaMapBuilder, TileType};
+use rltk::RandomNumberGenerator;
+
+/// Puts the way down in the middle of the last room.
+pub struct RoomBasedStairs {}
+
+impl MetaMapBuilder for RoomBasedStairs {
+    fn build_map(&mut self, _rng: &mut RandomNumberGenerator, build_data: &mut BuildData) {
+        let rooms = build_data
+            .rooms
+            .as_ref()
+            .expect("Room based stairs need rooms");
+        let (x, y) = rooms[rooms.len() - 1].center();
+        let idx = build_data.map.xy_idx(x, y);
+        build_data.map.tiles[idx] = TileType::DownStairs;
+    }
+}
+
+impl RoomBasedStairs {
+    pub fn new() -> Box<RoomBasedStairs> {
+        Box::new(RoomBasedStairs {})
+    }
+}
+
+/// Puts the way down on the floor tile furthest from the starting position.
+pub struct DistantStairs {}
+
+impl MetaMapBuilder for DistantStairs {
+    fn build_map(&mut self, _rng: &mut RandomNumberGenerator, build_data: &mut BuildData) {
+        let start = build_data
+            .starting_position
+            .as_ref()
+            .expect("Distant stairs need a starting position");
+        let map = &mut build_data.map;
+        let distances = FlowMap::build(map, &[(map.xy_idx(start.x, start.y), 0)]);
+        let furthest = (0..map.tiles.len())
+            .filter(|idx| map.tiles[*idx] == TileType::Floor)
+            .filter_map(|idx| distances.distance(idx).map(|distance| (distance, idx)))
+            .max();
+        if let Some((_distance, idx)) = furthest {
+            map.tiles[idx] = TileType::DownStairs;
+        }
+    }
+}
+
+impl DistantStairs {
+    pub fn new() -> Box<DistantStairs> {
+        Box::new(DistantStairs {})
+    }
+}
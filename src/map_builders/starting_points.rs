@@ -0,0 +1,66 @@
+use super::{BuildData, FlowMap, MetaMapBuilder, Position, TileType};
+use rltk::RandomNumberGenerator;
+
+/// Starts the player in the middle of the first room.
+pub struct RoomBasedStartingPosition {}
+
+impl MetaMapBuilder for RoomBasedStartingPosition {
+    fn build_map(&mut self, _rng: &mut RandomNumberGenerator, build_data: &mut BuildData) {
+        let rooms = build_data
+            .rooms
+            .as_ref()
+            .expect("Room based starting position needs rooms");
+        let (x, y) = rooms[0].center();
+        build_data.starting_position = Some(Position { x, y });
+    }
+}
+
+impl RoomBasedStartingPosition {
+    pub fn new() -> Box<RoomBasedStartingPosition> {
+        Box::new(RoomBasedStartingPosition {})
+    }
+}
+
+/// Starts the player in the largest open area, on the floor nearest the middle of the
+/// map, so that culling what can't be reached leaves as much of the level as possible.
+pub struct AreaStartingPosition {}
+
+impl MetaMapBuilder for AreaStartingPosition {
+    fn build_map(&mut self, _rng: &mut RandomNumberGenerator, build_data: &mut BuildData) {
+        let map = &build_data.map;
+
+        // Flood out from every floor tile not yet in an area to find them all
+        let mut in_area = vec![false; map.tiles.len()];
+        let mut largest: Vec<usize> = Vec::new();
+        for idx in 0..map.tiles.len() {
+            if map.tiles[idx] != TileType::Floor || in_area[idx] {
+                continue;
+            }
+            let flow = FlowMap::build(map, &[(idx, 0)]);
+            let area: Vec<usize> = (0..map.tiles.len())
+                .filter(|tile| flow.distance(*tile).is_some())
+                .collect();
+            for tile in area.iter() {
+                in_area[*tile] = true;
+            }
+            if area.len() > largest.len() {
+                largest = area;
+            }
+        }
+
+        let centre = Position { x: map.width / 2, y: map.height / 2 };
+        let nearest = largest
+            .into_iter()
+            .filter(|idx| map.tiles[*idx] == TileType::Floor)
+            .map(|idx| Position { x: idx as i32 % map.width, y: idx as i32 / map.width })
+            .min_by_key(|pos| pos.square_distance(&centre))
+            .expect("There's no floor to start on");
+        build_data.starting_position = Some(nearest);
+    }
+}
+
+impl AreaStartingPosition {
+    pub fn new() -> Box<AreaStartingPosition> {
+        Box::new(AreaStartingPosition {})
+    }
+}
@@ -0,0 +1,112 @@
+use super::{draw_tiles, Map};
+use rltk::{Rltk, VirtualKeyCode, RGB};
+
+/// How long each snapshot stays up at normal speed, in milliseconds.
+const FRAME_MS: f32 = 100.0;
+
+/// How much faster or slower each press of up or down makes the playback.
+const SPEED_STEP: f32 = 2.0;
+
+/// Plays back the snapshots taken while a level was being built, so you can watch how
+/// each builder in the chain changes the map. Turned on with `--mapgen`, and then shown
+/// in place of the game whenever a new level is made.
+///
+/// Space pauses, the left and right arrows step through the snapshots, up and down
+/// change the speed, and escape or return carries on with the game.
+pub struct MapGenVisualiser {
+    pub enabled: bool,
+    history: Vec<Map>,
+    index: usize,
+    timer: f32,
+    speed: f32,
+    paused: bool,
+}
+
+impl MapGenVisualiser {
+    pub fn new(enabled: bool) -> MapGenVisualiser {
+        MapGenVisualiser {
+            enabled,
+            history: Vec::new(),
+            index: 0,
+            timer: 0.0,
+            speed: 1.0,
+            paused: false,
+        }
+    }
+
+    /// Starts playing a new level's snapshots from the beginning.
+    pub fn start(&mut self, history: Vec<Map>) {
+        self.history = history;
+        self.index = 0;
+        self.timer = 0.0;
+        self.paused = false;
+    }
+
+    pub fn is_playing(&self) -> bool {
+        !self.history.is_empty()
+    }
+
+    /// Draws the current snapshot and deals with the controls, moving on to the next
+    /// snapshot when it's time. Holds on the last one until told to carry on.
+    pub fn tick(&mut self, ctx: &mut Rltk) {
+        let last = self.history.len() - 1;
+        match ctx.key {
+            Some(VirtualKeyCode::Escape) | Some(VirtualKeyCode::Return) => {
+                self.history.clear();
+                return;
+            }
+            Some(VirtualKeyCode::Space) => self.paused = !self.paused,
+            Some(VirtualKeyCode::Left) => {
+                self.paused = true;
+                self.index = self.index.saturating_sub(1);
+            }
+            Some(VirtualKeyCode::Right) => {
+                self.paused = true;
+                self.index = usize::min(self.index + 1, last);
+            }
+            Some(VirtualKeyCode::Up) => self.speed *= SPEED_STEP,
+            Some(VirtualKeyCode::Down) => self.speed /= SPEED_STEP,
+            Some(VirtualKeyCode::Home) => self.index = 0,
+            _ => {}
+        }
+
+        if !self.paused && self.index < last {
+            self.timer += ctx.frame_time_ms * self.speed;
+            while self.timer > FRAME_MS && self.index < last {
+                self.timer -= FRAME_MS;
+                self.index += 1;
+            }
+        }
+
+        draw_tiles(&self.history[self.index], ctx);
+
+        let status = if self.paused {
+            "paused".to_string()
+        } else if self.index == last {
+            "done".to_string()
+        } else {
+            format!("{}x speed", self.speed)
+        };
+        ctx.print_color(
+            1,
+            44,
+            RGB::named(rltk::YELLOW),
+            RGB::named(rltk::BLACK),
+            format!("Building the map: {} of {} ({})", self.index + 1, last + 1, status),
+        );
+        ctx.print_color(
+            1,
+            46,
+            RGB::named(rltk::WHITE),
+            RGB::named(rltk::BLACK),
+            "SPACE pause  LEFT/RIGHT step  UP/DOWN speed  HOME restart",
+        );
+        ctx.print_color(
+            1,
+            47,
+            RGB::named(rltk::WHITE),
+            RGB::named(rltk::BLACK),
+            "ESCAPE or RETURN to carry on",
+        );
+    }
+}
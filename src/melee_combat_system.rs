@@ -1,10 +1,46 @@
 use super::{
-    gamelog::GameLog, particle_system::ParticleBuilder, CombatStats, DefenseBonus, Equipped,
-    MeleePowerBonus, Name, Position, SufferDamage, WantsToMelee,
+    gamelog::GameLog,
+    noise_system::{NoiseKind, Noises},
+    particle_system::ParticleBuilder,
+    Allegiance, Attributes, CombatStats, DamageType, DefenseBonus, Dodging, Equipped, Exhausted,
+    InflictsStatus, Knockback, MeleePowerBonus, Name, NaturalAttack, Position, PowerAttack,
+    Reaction, StatusEffects, SufferDamage, WantsToMelee, WeaponStats,
 };
-use rltk::RandomNumberGenerator;
+use rltk::{Point, RandomNumberGenerator};
 use specs::prelude::*;
 
+/// Attacks at least this strong will knock a weapon out of the way, or shove back
+/// a weaker creature.
+const HEAVY_HIT_POWER: i32 = 6;
+
+/// What an attacker has to roll on a d20 to hit a defender with no dexterity bonus.
+const BASE_TO_HIT: i32 = 10;
+
+/// To-hit penalty for attacking, and bonus for being attacked, while exhausted.
+const EXHAUSTED_PENALTY: i32 = 2;
+
+/// To-hit bonus against a target that dodged towards the attacker.
+const DODGE_INTO_BONUS: i32 = 4;
+
+/// Extra damage from a stamina-fuelled power attack.
+const POWER_ATTACK_BONUS: i32 = 3;
+
+/// Turns a creature that wasn't hostile to the player against them, returning whether it
+/// changed its mind.
+pub fn provoke(allegiances: &mut WriteStorage<Allegiance>, victim: Entity, player: Entity) -> bool {
+    let player_allegiance = match allegiances.get(player) {
+        None => return false,
+        Some(allegiance) => allegiance.clone(),
+    };
+    match allegiances.get_mut(victim) {
+        Some(allegiance) if allegiance.reaction_to(&player_allegiance) != Reaction::Hostile => {
+            allegiance.angered = true;
+            true
+        }
+        _ => false,
+    }
+}
+
 pub struct MeleeCombatSystem {}
 
 impl<'a> System<'a> for MeleeCombatSystem {
@@ -22,6 +58,18 @@ impl<'a> System<'a> for MeleeCombatSystem {
         WriteExpect<'a, ParticleBuilder>,
         ReadStorage<'a, Position>,
         WriteExpect<'a, RandomNumberGenerator>,
+        WriteStorage<'a, Knockback>,
+        ReadStorage<'a, WeaponStats>,
+        ReadStorage<'a, Attributes>,
+        ReadStorage<'a, Exhausted>,
+        WriteStorage<'a, PowerAttack>,
+        ReadStorage<'a, Dodging>,
+        ReadStorage<'a, NaturalAttack>,
+        ReadStorage<'a, InflictsStatus>,
+        WriteStorage<'a, StatusEffects>,
+        WriteExpect<'a, Noises>,
+        ReadExpect<'a, Entity>,
+        WriteStorage<'a, Allegiance>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -38,6 +86,18 @@ impl<'a> System<'a> for MeleeCombatSystem {
             mut particle_builder,
             positions,
             mut rng,
+            mut knockbacks,
+            weapon_stats,
+            attributes,
+            exhausted,
+            mut power_attacks,
+            dodging,
+            natural_attacks,
+            inflicts_status,
+            mut statuses,
+            mut noises,
+            player_entity,
+            mut allegiances,
         ) = data;
 
         for (entity, wants_melee, name, stats) in
@@ -56,6 +116,9 @@ impl<'a> System<'a> for MeleeCombatSystem {
                 let target_stats = combat_stats.get(wants_melee.target).unwrap();
                 if target_stats.hp > 0 {
                     let target_name = names.get(wants_melee.target).unwrap();
+                    if let Some(target_pos) = positions.get(wants_melee.target) {
+                        noises.make(Point::new(target_pos.x, target_pos.y), NoiseKind::Fighting);
+                    }
 
                     let mut defensive_bonus = 0;
                     for (_item_entity, defense_bonus, equipped_by) in
@@ -66,6 +129,76 @@ impl<'a> System<'a> for MeleeCombatSystem {
                         }
                     }
 
+                    // A dodging target can only be caught by attacks it dodged into
+                    let mut dodged_into = false;
+                    if let Some(dodge) = dodging.get(wants_melee.target) {
+                        let attacker_pos = positions.get(entity).unwrap();
+                        let target_pos = positions.get(wants_melee.target).unwrap();
+                        let (ax, ay) =
+                            (attacker_pos.x - target_pos.x, attacker_pos.y - target_pos.y);
+                        let in_reach = i32::max(i32::abs(ax), i32::abs(ay))
+                            <= weapon_stats.get(entity).map_or(1, |w| w.reach);
+                        dodged_into = ax * dodge.dx + ay * dodge.dy > 0;
+
+                        if !in_reach {
+                            log.entries.push(format!(
+                                "{} swings at where {} was.",
+                                &name.name, &target_name.name
+                            ));
+                            continue;
+                        }
+                        if !dodged_into {
+                            particle_builder.request(
+                                target_pos.x,
+                                target_pos.y,
+                                rltk::RGB::named(rltk::CYAN),
+                                rltk::RGB::named(rltk::BLACK),
+                                rltk::to_cp437('~'),
+                                200.0,
+                            );
+                            log.entries.push(format!(
+                                "{} dodges the {}'s attack.",
+                                &target_name.name, &name.name
+                            ));
+                            continue;
+                        }
+                    }
+
+                    // Roll to hit: d20 plus the attacker's dexterity, against the defender's
+                    let attacker = wielder(entity, &weapon_stats);
+                    let defender = wielder(wants_melee.target, &weapon_stats);
+                    let attacker_attributes = attributes.get(attacker);
+                    let defender_attributes = attributes.get(defender);
+                    let mut hit_bonus = attacker_attributes.map_or(0, |a| a.dexterity.bonus);
+                    if dodged_into {
+                        log.entries.push(format!(
+                            "{} dodges straight into the {}!",
+                            &target_name.name, &name.name
+                        ));
+                        hit_bonus += DODGE_INTO_BONUS;
+                    }
+                    let strength_bonus = attacker_attributes.map_or(0, |a| a.strength.bonus);
+                    let mut needed =
+                        BASE_TO_HIT + defender_attributes.map_or(0, |a| a.dexterity.bonus);
+                    if exhausted.get(attacker).is_some() {
+                        hit_bonus -= EXHAUSTED_PENALTY;
+                    }
+                    if exhausted.get(defender).is_some() {
+                        needed -= EXHAUSTED_PENALTY;
+                    }
+
+                    let natural_roll = rng.roll_dice(1, 20);
+                    let roll = natural_roll + hit_bonus;
+                    let odds = format!("rolled {}, needed {}", roll, needed);
+                    let critical = natural_roll == 20;
+                    if natural_roll == 1 || (roll < needed && !critical) {
+                        log.entries.push(format!(
+                            "{} misses {} ({}).",
+                            &name.name, &target_name.name, odds
+                        ));
+                        continue;
+                    }
+
                     let pos = positions.get(wants_melee.target);
                     if let Some(pos) = pos {
                         let char = match rng.roll_dice(1, 7) {
@@ -87,27 +220,159 @@ impl<'a> System<'a> for MeleeCombatSystem {
                         );
                     }
 
-                    let damage = i32::max(
-                        0,
-                        (stats.power + offensive_bonus) - (target_stats.defense + defensive_bonus),
-                    );
+                    // Anything the player hits takes it personally
+                    let by_player = entity == *player_entity
+                        || weapon_stats
+                            .get(entity)
+                            .is_some_and(|w| w.owner == *player_entity);
+                    if by_player && provoke(&mut allegiances, wants_melee.target, *player_entity) {
+                        log.entries
+                            .push(format!("{} turns on you!", &target_name.name));
+                    }
+
+                    // Weapons wear with use
+                    if weapon_stats.get(entity).is_some() && rng.roll_dice(1, 4) == 1 {
+                        SufferDamage::new_damage(&mut inflict_damage, entity, 1, DamageType::Blunt);
+                    }
+
+                    let mut attack_power = stats.power + offensive_bonus + strength_bonus;
+                    if power_attacks.get(entity).is_some() {
+                        attack_power += POWER_ATTACK_BONUS;
+                        log.entries
+                            .push(format!("{} puts everything into the blow!", &name.name));
+                    }
+                    if weapon_stats.get(wants_melee.target).is_some() {
+                        // Blows against a weapon wear it down rather than getting past its guard
+                        let wear = i32::max(1, attack_power / 3);
+                        log.entries.push(format!(
+                            "{} strikes {}, wearing it down ({}).",
+                            &name.name, &target_name.name, odds
+                        ));
+                        SufferDamage::new_damage(
+                            &mut inflict_damage,
+                            wants_melee.target,
+                            wear,
+                            DamageType::Blunt,
+                        );
+
+                        // Weapons are light, and go flying if hit hard enough
+                        if attack_power >= HEAVY_HIT_POWER {
+                            let distance = rng.roll_dice(1, 3);
+                            knock_back(
+                                &mut knockbacks,
+                                &positions,
+                                entity,
+                                wants_melee.target,
+                                distance,
+                            );
+                        }
+                        continue;
+                    }
+
+                    let damage_type = match weapon_stats.get(entity) {
+                        Some(weapon) => weapon.damage_type,
+                        None => natural_attacks
+                            .get(entity)
+                            .map_or(DamageType::Blunt, |a| a.damage_type),
+                    };
+                    let mut damage =
+                        i32::max(0, attack_power - (target_stats.defense + defensive_bonus));
+                    if critical {
+                        damage *= 2;
+                    }
 
                     if damage == 0 {
                         log.entries.push(format!(
-                            "{} is unable to hurt {}",
-                            &name.name, &target_name.name
+                            "{} is unable to hurt {} ({}).",
+                            &name.name, &target_name.name, odds
+                        ));
+                    } else if critical {
+                        log.entries.push(format!(
+                            "{} critically hits {}, for {} hp ({}).",
+                            &name.name, &target_name.name, damage, odds
                         ));
+                        SufferDamage::new_damage(
+                            &mut inflict_damage,
+                            wants_melee.target,
+                            damage,
+                            damage_type,
+                        );
                     } else {
                         log.entries.push(format!(
-                            "{} hits {}, for {} hp.",
-                            &name.name, &target_name.name, damage
+                            "{} hits {}, for {} hp ({}).",
+                            &name.name, &target_name.name, damage, odds
                         ));
-                        SufferDamage::new_damage(&mut inflict_damage, wants_melee.target, damage);
+                        SufferDamage::new_damage(
+                            &mut inflict_damage,
+                            wants_melee.target,
+                            damage,
+                            damage_type,
+                        );
+                    }
+
+                    // Blows that land can leave something nasty behind
+                    let status = match weapon_stats.get(entity) {
+                        Some(weapon) => inflicts_status.get(weapon.item),
+                        None => inflicts_status.get(entity),
+                    };
+                    if let Some(status) = status {
+                        if damage > 0 && rng.roll_dice(1, 100) <= status.chance {
+                            log.entries.push(format!(
+                                "{} is {}!",
+                                &target_name.name,
+                                status.kind.name().to_lowercase()
+                            ));
+                            StatusEffects::add_effect(
+                                &mut statuses,
+                                wants_melee.target,
+                                status.kind,
+                                status.turns,
+                                status.potency,
+                            );
+                        }
+                    }
+
+                    // A heavy blow from something stronger shoves the target back a tile
+                    let strength = |a: Option<&Attributes>| {
+                        a.map_or(10, |a| a.strength.base + a.strength.modifiers)
+                    };
+                    if attack_power >= HEAVY_HIT_POWER
+                        && strength(attacker_attributes) > strength(defender_attributes)
+                    {
+                        log.entries
+                            .push(format!("{} is knocked back by the blow.", &target_name.name));
+                        knock_back(&mut knockbacks, &positions, entity, wants_melee.target, 1);
                     }
                 }
             }
         }
 
         wants_melee.clear();
+        power_attacks.clear();
+    }
+}
+
+/// Weapons fight with the attributes and condition of whoever is wielding them.
+fn wielder(entity: Entity, weapon_stats: &ReadStorage<WeaponStats>) -> Entity {
+    weapon_stats.get(entity).map_or(entity, |w| w.owner)
+}
+
+/// Pushes the target directly away from whoever hit it.
+fn knock_back(
+    knockbacks: &mut WriteStorage<Knockback>,
+    positions: &ReadStorage<Position>,
+    attacker: Entity,
+    target: Entity,
+    distance: i32,
+) {
+    let attacker_pos = positions.get(attacker).unwrap();
+    let target_pos = positions.get(target).unwrap();
+    let dx = i32::signum(target_pos.x - attacker_pos.x);
+    let dy = i32::signum(target_pos.y - attacker_pos.y);
+    if dx == 0 && dy == 0 {
+        return;
     }
+    knockbacks
+        .insert(target, Knockback { dx, dy, distance })
+        .expect("Unable to insert knockback");
 }
@@ -1,4 +1,17 @@
-use super::{Confusion, EntityMoved, Map, Monster, Position, RunState, Viewshed, WantsToMelee};
+use super::{
+    flow_map_system::{FlowMap, FlowMaps},
+    gamelog::GameLog,
+    pack_system::{PackOrder, PackPlans},
+    ranged_combat_system::line_of_fire,
+    stamina_system::{spend_stamina, StaminaAction},
+    status_effect_system::{is_affected, BREAK_FREE_DC},
+    telegraph_system::WIND_UP_TURNS,
+    Alerted, Allegiance, Ammo, AreaOfEffect, Attributes, Behaviour, Brain, CombatStats, Dodging,
+    EntityMoved, Equipped, Exhausted, InBackpack, Knockback, Map, Monster, Name, PendingAttack,
+    Position, PowerAttack, Ranged, RangedWeapon, Reaction, RunState, StatusEffects, StatusKind,
+    TileType, Viewshed, WantsToMelee, WantsToShoot, WantsToUseItem, WeaponStats, WindUpAttack,
+    WindUpKind, Wits,
+};
 use rltk::{Point, RandomNumberGenerator};
 use specs::prelude::*;
 
@@ -16,9 +29,35 @@ impl<'a> System<'a> for MonsterAI {
         ReadStorage<'a, Monster>,
         WriteStorage<'a, Position>,
         WriteStorage<'a, WantsToMelee>,
-        WriteStorage<'a, Confusion>,
+        WriteStorage<'a, StatusEffects>,
         WriteStorage<'a, EntityMoved>,
         WriteExpect<'a, RandomNumberGenerator>,
+        ReadStorage<'a, WeaponStats>,
+        WriteStorage<'a, CombatStats>,
+        WriteStorage<'a, Exhausted>,
+        WriteStorage<'a, PowerAttack>,
+        ReadStorage<'a, Dodging>,
+        WriteStorage<'a, Alerted>,
+        ReadStorage<'a, Attributes>,
+        WriteStorage<'a, Brain>,
+        WriteStorage<'a, Knockback>,
+        ReadStorage<'a, Name>,
+        WriteExpect<'a, GameLog>,
+        ReadExpect<'a, FlowMaps>,
+        ReadExpect<'a, PackPlans>,
+        (
+            ReadStorage<'a, InBackpack>,
+            ReadStorage<'a, Equipped>,
+            ReadStorage<'a, Ranged>,
+            ReadStorage<'a, RangedWeapon>,
+            ReadStorage<'a, Ammo>,
+            ReadStorage<'a, AreaOfEffect>,
+            WriteStorage<'a, WantsToShoot>,
+            WriteStorage<'a, WantsToUseItem>,
+            ReadStorage<'a, Allegiance>,
+            ReadStorage<'a, WindUpAttack>,
+            WriteStorage<'a, PendingAttack>,
+        ),
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -32,58 +71,827 @@ impl<'a> System<'a> for MonsterAI {
             monster,
             mut position,
             mut wants_to_melee,
-            mut confused,
+            mut statuses,
             mut entity_moved,
             mut rng,
+            weapon_stats,
+            mut combat_stats,
+            mut exhausted,
+            mut power_attacks,
+            dodging,
+            mut alerted,
+            attributes,
+            mut brains,
+            mut knockbacks,
+            names,
+            mut log,
+            flow_maps,
+            pack_plans,
+            (
+                backpack,
+                equipped,
+                ranged,
+                ranged_weapons,
+                ammo,
+                aoe,
+                mut wants_shoot,
+                mut wants_use,
+                allegiances,
+                wind_ups,
+                mut pending_attacks,
+            ),
         ) = data;
 
         if *runstate != RunState::MonsterTurn {
             return;
         }
 
+        let player_weapons: Vec<(Entity, Point)> = (&entities, &weapon_stats, &position)
+            .join()
+            .filter(|(_weapon, stats, _pos)| stats.owner == *player_entity)
+            .map(|(weapon, _stats, pos)| (weapon, Point::new(pos.x, pos.y)))
+            .collect();
+        let player_can_swing = exhausted.get(*player_entity).is_none()
+            && combat_stats
+                .get(*player_entity)
+                .is_some_and(|s| s.stamina >= StaminaAction::WeaponSwing.cost());
+        let danger =
+            weapon_danger(&map, *player_pos, &player_weapons, &weapon_stats, player_can_swing);
+        let player_strength = attributes
+            .get(*player_entity)
+            .map_or(0, |a| a.strength.bonus);
+
+        // Everything else that takes sides, for monsters picking fights with each other
+        let creatures: Vec<(Entity, Point)> = (&entities, &allegiances, &position)
+            .join()
+            .filter(|(creature, _allegiance, _pos)| *creature != *player_entity)
+            .map(|(creature, _allegiance, pos)| (creature, Point::new(pos.x, pos.y)))
+            .collect();
+
         for (entity, mut viewshed, _monster, mut pos) in
             (&entities, &mut viewshed, &monster, &mut position).join()
         {
             let mut can_act = true;
 
-            let is_confused = confused.get_mut(entity);
-            if let Some(i_am_confused) = is_confused {
-                i_am_confused.turns -= 1;
-                if i_am_confused.turns < 1 {
-                    confused.remove(entity);
+            // Trapped monsters can still swing, but spend their movement struggling
+            let mut can_move = true;
+            if let Some(held) = statuses.get_mut(entity) {
+                if held.has(StatusKind::Immobilised) {
+                    let freed = attributes
+                        .get(entity)
+                        .is_some_and(|a| a.strength.check(&mut rng, BREAK_FREE_DC));
+                    if freed {
+                        held.remove_effect(StatusKind::Immobilised);
+                    }
+                    can_move = false;
                 }
+            }
+
+            // Alarms tell monsters where the player is for a while
+            let mut is_alerted = false;
+            if let Some(alert) = alerted.get_mut(entity) {
+                alert.turns -= 1;
+                is_alerted = alert.turns > 0;
+                if !is_alerted {
+                    alerted.remove(entity);
+                }
+            }
+
+            if is_affected(&statuses, entity, StatusKind::Stun) {
                 can_act = false;
             }
 
-            if can_act {
-                let distance =
-                    rltk::DistanceAlg::Pythagoras.distance2d(Point::new(pos.x, pos.y), *player_pos);
-                if distance < 1.5 {
-                    wants_to_melee
-                        .insert(entity, WantsToMelee { target: *player_entity })
-                        .expect("Unable to insert attack");
-                } else if viewshed.visible_tiles.contains(&*player_pos) || rng.roll_dice(1, 8) == 1
-                {
-                    // Path to the player
-                    let path = rltk::a_star_search(
-                        map.xy_idx(pos.x, pos.y),
-                        map.xy_idx(player_pos.x, player_pos.y),
-                        &*map,
-                    );
-                    if path.success && path.steps.len() > 1 {
-                        let mut idx = map.xy_idx(pos.x, pos.y);
-                        map.blocked[idx] = false;
-                        pos.x = path.steps[1] as i32 % map.width;
-                        pos.y = path.steps[1] as i32 / map.width;
+            // Slowed monsters only act every other turn
+            let slow = statuses.get(entity).and_then(|s| s.get(StatusKind::Slow));
+            if slow.is_some_and(|slow| slow.turns % 2 == 1) {
+                can_act = false;
+            }
+
+            // Confused monsters stagger about instead of doing anything useful
+            if can_act && can_move && is_affected(&statuses, entity, StatusKind::Confusion) {
+                can_act = false;
+                let (x, y) = (pos.x + rng.range(-1, 2), pos.y + rng.range(-1, 2));
+                if x > 0 && x < map.width - 1 && y > 0 && y < map.height - 1 {
+                    let idx = map.xy_idx(x, y);
+                    if !map.blocked[idx] {
+                        let old_idx = map.xy_idx(pos.x, pos.y);
+                        map.blocked[old_idx] = false;
+                        pos.x = x;
+                        pos.y = y;
                         entity_moved
                             .insert(entity, EntityMoved {})
                             .expect("Unable to insert marker");
-                        idx = map.xy_idx(pos.x, pos.y);
                         map.blocked[idx] = true;
                         viewshed.dirty = true;
                     }
                 }
             }
+
+            if !can_act {
+                // Losing a turn spoils any attack that was winding up
+                pending_attacks.remove(entity);
+                continue;
+            }
+
+            // An attack that is winding up has to be seen through before anything else
+            if let Some(attack) = pending_attacks.get_mut(entity) {
+                attack.turns -= 1;
+                if attack.turns <= 0 {
+                    let attack = pending_attacks.remove(entity).unwrap();
+                    let (target, moved) =
+                        land_wind_up(&mut map, pos, entity, &attack, &combat_stats);
+                    let monster_name = names
+                        .get(entity)
+                        .map_or("Something".to_string(), |n| n.name.clone());
+                    match target {
+                        Some(target) => {
+                            wants_to_melee
+                                .insert(entity, WantsToMelee { target })
+                                .expect("Unable to insert attack");
+                            power_attacks
+                                .insert(entity, PowerAttack {})
+                                .expect("Unable to insert power attack");
+                        }
+                        None => log.entries.push(format!(
+                            "{}'s {} finds nothing there.",
+                            monster_name,
+                            attack.kind.name()
+                        )),
+                    }
+                    if moved {
+                        entity_moved
+                            .insert(entity, EntityMoved {})
+                            .expect("Unable to insert marker");
+                        viewshed.dirty = true;
+                    }
+                }
+                continue;
+            }
+
+            let brain = match brains.get_mut(entity) {
+                None => continue,
+                Some(brain) => brain,
+            };
+
+            // Decide what to do, based on what the monster knows
+            let here = Point::new(pos.x, pos.y);
+            let hostile = match (allegiances.get(entity), allegiances.get(*player_entity)) {
+                (Some(mine), Some(theirs)) => mine.reaction_to(theirs) == Reaction::Hostile,
+                _ => true,
+            };
+            let sees_player = hostile && viewshed.visible_tiles.contains(&*player_pos);
+            let is_alerted = hostile && is_alerted;
+            let (hurt, desperate) = combat_stats.get(entity).map_or((false, false), |s| {
+                (s.hp < s.max_hp, s.hp * 100 <= s.max_hp * brain.flee_below)
+            });
+            // Pack members know whatever the rest of their pack knows
+            let pack_order = pack_plans.order(entity);
+            let pack_hunting = hostile && pack_order.is_some_and(|order| order.is_hunting());
+            let following = match pack_order {
+                Some(PackOrder::Follow(leader_pos)) => Some(leader_pos),
+                _ => None,
+            };
+            let knows_player = sees_player || is_alerted || pack_hunting;
+            if knows_player {
+                brain.last_seen = Some(*player_pos);
+            }
+            let mut next = match brain.state {
+                Behaviour::Asleep => {
+                    let woken = is_alerted
+                        || pack_hunting
+                        || hurt
+                        || (sees_player && rng.roll_dice(1, WAKE_CHANCE) == 1);
+                    if woken {
+                        Behaviour::Hunt
+                    } else {
+                        Behaviour::Asleep
+                    }
+                }
+                Behaviour::Flee if sees_player => Behaviour::Flee,
+                Behaviour::Flee => Behaviour::Idle,
+                _ if knows_player => Behaviour::Hunt,
+                Behaviour::Hunt => {
+                    brain.search_turns = brain.patience;
+                    Behaviour::Search
+                }
+                Behaviour::Search if brain.search_turns > 0 => Behaviour::Search,
+                Behaviour::Search => Behaviour::Idle,
+                Behaviour::Wander if brain.wander_to.is_some() => Behaviour::Wander,
+                Behaviour::Wander => Behaviour::Idle,
+                Behaviour::Idle => {
+                    if rng.roll_dice(1, 100) <= brain.restlessness {
+                        brain.wander_to = wander_destination(&map, here, &mut rng);
+                        Behaviour::Wander
+                    } else {
+                        Behaviour::Idle
+                    }
+                }
+            };
+            // Being hurt wakes anything up, but only gets it hunting a player it's hostile to
+            if next == Behaviour::Hunt && !hostile {
+                next = Behaviour::Idle;
+            }
+            if next == Behaviour::Hunt && desperate {
+                next = Behaviour::Flee;
+            }
+            brain.state = next;
+
+            // A creature from a faction it hates makes a better target than a player who
+            // is further off, or nowhere to be seen
+            let prey = match brain.state {
+                Behaviour::Asleep | Behaviour::Flee => None,
+                _ => nearest_prey(entity, here, &creatures, &allegiances, viewshed),
+            };
+            let player_distance = rltk::DistanceAlg::Pythagoras.distance2d(here, *player_pos);
+            let prey = prey.filter(|(_prey, prey_pos)| {
+                !sees_player
+                    || rltk::DistanceAlg::Pythagoras.distance2d(here, *prey_pos) < player_distance
+            });
+
+            let mut moved = false;
+            match brain.state {
+                _ if prey.is_some() => {
+                    if let Some((target, prey_pos)) = prey {
+                        if rltk::DistanceAlg::Pythagoras.distance2d(here, prey_pos) < 1.5 {
+                            wants_to_melee
+                                .insert(entity, WantsToMelee { target })
+                                .expect("Unable to insert attack");
+                        } else if can_move {
+                            moved = step_towards(&mut map, pos, prey_pos);
+                        }
+                    }
+                }
+                Behaviour::Idle | Behaviour::Wander if following.is_some() => {
+                    // Keep up with the pack leader rather than going off alone
+                    if let (true, Some(leader_pos)) = (can_move, following) {
+                        moved = step_towards(&mut map, pos, leader_pos);
+                    }
+                }
+                Behaviour::Asleep | Behaviour::Idle => {}
+                Behaviour::Hunt if !hostile => {}
+                Behaviour::Hunt => {
+                    // The planner hasn't heard yet if this member only just spotted the
+                    // player, so an order to follow the leader doesn't hold it back
+                    let hunting_order =
+                        pack_order.filter(|order| !matches!(order, PackOrder::Follow(_)));
+                    let distance = rltk::DistanceAlg::Pythagoras.distance2d(here, *player_pos);
+                    let adjacent_weapon = player_weapons.iter().find(|(_weapon, weapon_pos)| {
+                        rltk::DistanceAlg::Pythagoras.distance2d(here, *weapon_pos) < 1.5
+                    });
+                    let swings_at_dodge = dodging.get(*player_entity).is_some_and(|dodge| {
+                        let from = Point::new(dodge.from_x, dodge.from_y);
+                        rltk::DistanceAlg::Pythagoras.distance2d(here, from) < 1.5
+                    });
+
+                    // Shoot or cast at the player when there's a clear line to them, and
+                    // otherwise don't let them get too close
+                    let attacks = ranged_attacks(
+                        entity,
+                        &entities,
+                        &backpack,
+                        &equipped,
+                        &ranged,
+                        &ranged_weapons,
+                        &ammo,
+                    );
+                    let in_line = distance >= 1.5
+                        && line_of_fire(&map, here, *player_pos, &[]).last() == Some(&*player_pos);
+                    let usable: Vec<&RangedAttack> = attacks
+                        .iter()
+                        .filter(|attack| {
+                            in_line
+                                && distance <= attack.range as f32
+                                && aoe
+                                    .get(attack.item)
+                                    .is_none_or(|area| distance > area.radius as f32)
+                        })
+                        .collect();
+
+                    // Now and then, wind up something heavier than a plain blow
+                    let wind_up = wind_ups
+                        .get(entity)
+                        .filter(|wind_up| rng.roll_dice(1, 100) <= wind_up.chance)
+                        .and_then(|wind_up| {
+                            wind_up_tiles(&map, wind_up.kind, here, *player_pos, can_move)
+                                .map(|tiles| (wind_up.kind, tiles))
+                        });
+
+                    if let Some((kind, tiles)) = wind_up {
+                        let monster_name = names
+                            .get(entity)
+                            .map_or("Something".to_string(), |n| n.name.clone());
+                        log.entries.push(match kind {
+                            WindUpKind::Smash => {
+                                format!("{} raises its fists high overhead!", monster_name)
+                            }
+                            WindUpKind::Charge => {
+                                format!("{} lowers its head to charge!", monster_name)
+                            }
+                        });
+                        pending_attacks
+                            .insert(entity, PendingAttack { kind, tiles, turns: WIND_UP_TURNS })
+                            .expect("Unable to insert pending attack");
+                    } else if !usable.is_empty() {
+                        let attack = usable[rng.range(0, usable.len() as i32) as usize];
+                        if attack.shoot {
+                            wants_shoot
+                                .insert(
+                                    entity,
+                                    WantsToShoot { weapon: attack.item, target: *player_pos },
+                                )
+                                .expect("Unable to insert shot");
+                        } else {
+                            wants_use
+                                .insert(
+                                    entity,
+                                    WantsToUseItem { item: attack.item, target: Some(*player_pos) },
+                                )
+                                .expect("Unable to insert intent");
+                        }
+                    } else if !attacks.is_empty()
+                        && can_move
+                        && distance < KEEP_AWAY
+                        && step_away(&mut map, pos, *player_pos, &danger)
+                    {
+                        moved = true;
+                    } else if distance < 1.5 {
+                        wants_to_melee
+                            .insert(entity, WantsToMelee { target: *player_entity })
+                            .expect("Unable to insert attack");
+
+                        // Now and then, put some stamina behind the blow
+                        if let Some(stats) = combat_stats.get_mut(entity) {
+                            if rng.roll_dice(1, 3) == 1
+                                && spend_stamina(
+                                    entity,
+                                    stats,
+                                    &mut exhausted,
+                                    StaminaAction::PowerAttack,
+                                )
+                            {
+                                power_attacks
+                                    .insert(entity, PowerAttack {})
+                                    .expect("Unable to insert power attack");
+                            }
+                        }
+                    } else if swings_at_dodge {
+                        // Too late to stop the blow aimed at where the player was
+                        wants_to_melee
+                            .insert(entity, WantsToMelee { target: *player_entity })
+                            .expect("Unable to insert attack");
+                    } else if let Some((weapon, weapon_pos)) = adjacent_weapon {
+                        let monster_name = names
+                            .get(entity)
+                            .map_or("Something".to_string(), |n| n.name.clone());
+                        if brain.wits == Wits::Cunning && rng.roll_dice(1, 2) == 1 {
+                            // Try to wrench the weapon out of the player's grip
+                            let wrenched = attributes.get(entity).is_some_and(|a| {
+                                a.strength.check(&mut rng, GRAB_DC + player_strength)
+                            });
+                            if wrenched {
+                                log.entries
+                                    .push(format!("{} wrenches at your weapon!", monster_name));
+                                knockbacks
+                                    .insert(
+                                        *weapon,
+                                        Knockback {
+                                            dx: (weapon_pos.x - player_pos.x).signum(),
+                                            dy: (weapon_pos.y - player_pos.y).signum(),
+                                            distance: 2,
+                                        },
+                                    )
+                                    .expect("Unable to insert knockback");
+                            } else {
+                                log.entries.push(format!(
+                                    "{} grabs at your weapon, but you hold on.",
+                                    monster_name
+                                ));
+                            }
+                        } else {
+                            // The weapon is in the way, so go through it
+                            wants_to_melee
+                                .insert(entity, WantsToMelee { target: *weapon })
+                                .expect("Unable to insert attack");
+                        }
+                    } else if let (true, Some(order)) = (can_move, hunting_order) {
+                        moved = match order {
+                            PackOrder::Hold | PackOrder::Follow(_) => false,
+                            PackOrder::Gather(goal) => step_towards(&mut map, pos, goal),
+                            PackOrder::Surround(goal) if goal == here => false,
+                            PackOrder::Surround(goal) => {
+                                step_avoiding(&mut map, pos, goal, &danger)
+                                    || step_towards(&mut map, pos, goal)
+                            }
+                        };
+                    } else if can_move && brain.wits == Wits::Mindless {
+                        // A weapon held out closer than the player makes for tempting bait
+                        let mut goal = *player_pos;
+                        for (_weapon, weapon_pos) in player_weapons.iter() {
+                            let closer = rltk::DistanceAlg::Pythagoras
+                                .distance2d(here, *weapon_pos)
+                                < rltk::DistanceAlg::Pythagoras.distance2d(here, goal);
+                            if closer && viewshed.visible_tiles.contains(weapon_pos) {
+                                goal = *weapon_pos;
+                            }
+                        }
+                        moved = if goal == *player_pos {
+                            step_downhill(&mut map, pos, &flow_maps.to_player, &[])
+                        } else {
+                            step_towards(&mut map, pos, goal)
+                        };
+                    } else if can_move && distance > CLOSE_QUARTERS {
+                        // Far off, just follow everyone else's way to the player
+                        moved = step_downhill(&mut map, pos, &flow_maps.to_player, &danger);
+                    } else if can_move {
+                        // Cunning monsters go for the weapon itself, the rest try to get
+                        // around behind it
+                        let visible_weapon = player_weapons.iter().find(|(_weapon, weapon_pos)| {
+                            viewshed.visible_tiles.contains(weapon_pos)
+                        });
+                        let goal = match visible_weapon {
+                            Some((_weapon, weapon_pos)) if brain.wits == Wits::Cunning => {
+                                *weapon_pos
+                            }
+                            Some((_weapon, weapon_pos)) => {
+                                flank_point(&map, *player_pos, *weapon_pos).unwrap_or(*player_pos)
+                            }
+                            None => *player_pos,
+                        };
+
+                        // Keep out of reach if possible, and wait for an opening if not
+                        moved = step_avoiding(&mut map, pos, goal, &danger);
+                        if !moved
+                            && (danger.contains(&here) || rng.roll_dice(1, CHARGE_CHANCE) == 1)
+                        {
+                            moved = step_towards(&mut map, pos, goal);
+                        }
+                    }
+                }
+                Behaviour::Search => {
+                    // Head for where the player was last seen, then poke around nearby
+                    brain.search_turns -= 1;
+                    if can_move {
+                        match brain.last_seen {
+                            Some(goal) if goal != here => {
+                                moved = step_towards(&mut map, pos, goal);
+                                if !moved {
+                                    brain.last_seen = None;
+                                }
+                            }
+                            _ => {
+                                brain.last_seen = None;
+                                let step =
+                                    Point::new(pos.x + rng.range(-1, 2), pos.y + rng.range(-1, 2));
+                                moved = step_to(&mut map, pos, step);
+                            }
+                        }
+                    }
+                }
+                Behaviour::Wander => {
+                    if can_move {
+                        if let Some(goal) = brain.wander_to {
+                            moved = step_towards(&mut map, pos, goal);
+                            if !moved || (pos.x == goal.x && pos.y == goal.y) {
+                                brain.wander_to = None;
+                            }
+                        }
+                    }
+                }
+                Behaviour::Flee => {
+                    // Run for the stairs if they are nearer than the player, otherwise just
+                    // get away, and only fight when cornered
+                    let distance = rltk::DistanceAlg::Pythagoras.distance2d(here, *player_pos);
+                    let idx = map.xy_idx(pos.x, pos.y);
+                    if map.tiles[idx] == TileType::DownStairs {
+                        if map.visible_tiles[idx] {
+                            let monster_name = names
+                                .get(entity)
+                                .map_or("Something".to_string(), |n| n.name.clone());
+                            log.entries
+                                .push(format!("{} escapes down the stairs!", monster_name));
+                        }
+                        map.blocked[idx] = false;
+                        entities.delete(entity).expect("Unable to delete monster");
+                        continue;
+                    }
+                    let to_stairs = flow_maps.to_stairs.distance(idx);
+                    let flow =
+                        if to_stairs.is_some() && to_stairs < flow_maps.to_player.distance(idx) {
+                            &flow_maps.to_stairs
+                        } else {
+                            &flow_maps.flee
+                        };
+                    if can_move {
+                        moved = step_downhill(&mut map, pos, flow, &[]);
+                    }
+                    if !moved && distance < 1.5 {
+                        wants_to_melee
+                            .insert(entity, WantsToMelee { target: *player_entity })
+                            .expect("Unable to insert attack");
+                    }
+                }
+            }
+
+            if moved {
+                entity_moved
+                    .insert(entity, EntityMoved {})
+                    .expect("Unable to insert marker");
+                viewshed.dirty = true;
+            }
+        }
+    }
+}
+
+/// The chance, as 1 in this many, that a sleeping monster notices the player each turn.
+const WAKE_CHANCE: i32 = 3;
+
+/// The strength check, before the player's own strength, to wrench their weapon away.
+const GRAB_DC: i32 = 14;
+
+/// How close a monster has to be to the player before it starts thinking about the
+/// weapon, rather than just following the flow map in.
+const CLOSE_QUARTERS: f32 = 5.0;
+
+/// The chance, as 1 in this many, that a monster kept at bay by the weapon charges anyway.
+const CHARGE_CHANCE: i32 = 4;
+
+/// How close a monster with a ranged attack lets the player get before backing off.
+const KEEP_AWAY: f32 = 3.0;
+
+/// How far off a monster will start a charge from.
+const CHARGE_RANGE: f32 = 5.0;
+
+/// How far a monster sets off when it wanders.
+const WANDER_DISTANCE: i32 = 8;
+
+const NEIGHBOURS: [(i32, i32); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+/// The tiles a wind-up attack would land on, if the monster is placed to make it: a smash
+/// needs the player right next to it, and a charge a clear run at them from a little way
+/// off.
+fn wind_up_tiles(
+    map: &Map,
+    kind: WindUpKind,
+    from: Point,
+    player: Point,
+    can_move: bool,
+) -> Option<Vec<Point>> {
+    let distance = rltk::DistanceAlg::Pythagoras.distance2d(from, player);
+    match kind {
+        WindUpKind::Smash if distance < 1.5 => Some(vec![player]),
+        WindUpKind::Charge if can_move && (2.0..=CHARGE_RANGE).contains(&distance) => {
+            let path = line_of_fire(map, from, player, &[]);
+            if path.last() == Some(&player) {
+                Some(path)
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Lands a wound-up attack on the first creature standing in its tiles, with a charging
+/// monster running along them until it reaches it. Returns who is hit, if anyone, and
+/// whether the monster moved.
+fn land_wind_up(
+    map: &mut Map,
+    pos: &mut Position,
+    attacker: Entity,
+    attack: &PendingAttack,
+    combat_stats: &WriteStorage<CombatStats>,
+) -> (Option<Entity>, bool) {
+    let mut moved = false;
+    for tile in attack.tiles.iter() {
+        let idx = map.xy_idx(tile.x, tile.y);
+        let target = map.tile_content[idx]
+            .iter()
+            .find(|e| **e != attacker && combat_stats.get(**e).is_some());
+        if let Some(target) = target {
+            return (Some(*target), moved);
+        }
+        if attack.kind == WindUpKind::Charge {
+            if !step_to(map, pos, *tile) {
+                break;
+            }
+            moved = true;
+        }
+    }
+    (None, moved)
+}
+
+/// Whether a monster could step onto a tile.
+fn can_step(map: &Map, step: Point) -> bool {
+    step.x > 0
+        && step.x < map.width - 1
+        && step.y > 0
+        && step.y < map.height - 1
+        && !map.blocked[map.xy_idx(step.x, step.y)]
+}
+
+/// Moves a monster onto a free tile, keeping the map's blocking up to date.
+fn step_to(map: &mut Map, pos: &mut Position, step: Point) -> bool {
+    if !can_step(map, step) {
+        return false;
+    }
+    let old_idx = map.xy_idx(pos.x, pos.y);
+    map.blocked[old_idx] = false;
+    pos.x = step.x;
+    pos.y = step.y;
+    let idx = map.xy_idx(pos.x, pos.y);
+    map.blocked[idx] = true;
+    true
+}
+
+/// The first step on a path to the goal, which may be standing on a blocked tile.
+fn first_step(map: &mut Map, from: &Position, goal: Point) -> Option<Point> {
+    let goal_idx = map.xy_idx(goal.x, goal.y);
+    let goal_blocked = map.blocked[goal_idx];
+    map.blocked[goal_idx] = false;
+    let path = rltk::a_star_search(map.xy_idx(from.x, from.y), goal_idx, &*map);
+    map.blocked[goal_idx] = goal_blocked;
+    if path.success && path.steps.len() > 1 {
+        Some(Point::new(path.steps[1] as i32 % map.width, path.steps[1] as i32 / map.width))
+    } else {
+        None
+    }
+}
+
+/// Takes one step downhill on a flow map, keeping clear of the tiles to avoid.
+fn step_downhill(map: &mut Map, pos: &mut Position, flow: &FlowMap, avoid: &[Point]) -> bool {
+    let idx = map.xy_idx(pos.x, pos.y);
+    match flow.downhill(map, idx, avoid) {
+        Some(next) => {
+            step_to(map, pos, Point::new(next as i32 % map.width, next as i32 / map.width))
+        }
+        None => false,
+    }
+}
+
+/// Takes one step along a path to the goal.
+fn step_towards(map: &mut Map, pos: &mut Position, goal: Point) -> bool {
+    match first_step(map, pos, goal) {
+        Some(step) => step_to(map, pos, step),
+        None => false,
+    }
+}
+
+/// Takes one step along a path to the goal that keeps clear of the dangerous tiles.
+fn step_avoiding(map: &mut Map, pos: &mut Position, goal: Point, danger: &[Point]) -> bool {
+    let mut avoided: Vec<usize> = Vec::new();
+    for point in danger.iter().filter(|p| **p != goal) {
+        let idx = map.xy_idx(point.x, point.y);
+        if !map.blocked[idx] {
+            map.blocked[idx] = true;
+            avoided.push(idx);
+        }
+    }
+    let step = first_step(map, pos, goal);
+    for idx in avoided {
+        map.blocked[idx] = false;
+    }
+    match step {
+        Some(step) => step_to(map, pos, step),
+        None => false,
+    }
+}
+
+/// Steps to whichever neighbouring tile is furthest from `from`, so long as it is further
+/// away than where the monster is now and out of danger.
+fn step_away(map: &mut Map, pos: &mut Position, from: Point, danger: &[Point]) -> bool {
+    let here = Point::new(pos.x, pos.y);
+    let mut best = (here, rltk::DistanceAlg::Pythagoras.distance2d(here, from));
+    for (dx, dy) in NEIGHBOURS.iter() {
+        let step = Point::new(pos.x + dx, pos.y + dy);
+        let distance = rltk::DistanceAlg::Pythagoras.distance2d(step, from);
+        if distance > best.1 && can_step(map, step) && !danger.contains(&step) {
+            best = (step, distance);
+        }
+    }
+    best.0 != here && step_to(map, pos, best.0)
+}
+
+/// The nearest creature in view that a monster is hostile to, other than the player.
+fn nearest_prey(
+    hunter: Entity,
+    here: Point,
+    creatures: &[(Entity, Point)],
+    allegiances: &ReadStorage<Allegiance>,
+    viewshed: &Viewshed,
+) -> Option<(Entity, Point)> {
+    let mine = allegiances.get(hunter)?;
+    creatures
+        .iter()
+        .filter(|(creature, pos)| {
+            *creature != hunter
+                && viewshed.visible_tiles.contains(pos)
+                && allegiances
+                    .get(*creature)
+                    .is_some_and(|theirs| mine.reaction_to(theirs) == Reaction::Hostile)
+        })
+        .min_by_key(|(_creature, pos)| {
+            (pos.x - here.x) * (pos.x - here.x) + (pos.y - here.y) * (pos.y - here.y)
+        })
+        .copied()
+}
+
+/// Something a monster is carrying that it can attack with from a distance.
+struct RangedAttack {
+    item: Entity,
+    range: i32,
+    /// Fired through `WantsToShoot`, rather than used like a scroll
+    shoot: bool,
+}
+
+/// Every ranged attack a monster has to hand: a launcher it is wielding with ammunition
+/// to go with it, and whatever it can throw or read from its pack.
+fn ranged_attacks(
+    owner: Entity,
+    entities: &Entities,
+    backpack: &ReadStorage<InBackpack>,
+    equipped: &ReadStorage<Equipped>,
+    ranged: &ReadStorage<Ranged>,
+    ranged_weapons: &ReadStorage<RangedWeapon>,
+    ammo: &ReadStorage<Ammo>,
+) -> Vec<RangedAttack> {
+    let mut attacks: Vec<RangedAttack> = Vec::new();
+    for (item, held, weapon) in (entities, equipped, ranged_weapons).join() {
+        let loaded = (ammo, backpack)
+            .join()
+            .any(|(a, pack)| pack.owner == owner && a.kind == weapon.ammo);
+        if held.owner == owner && loaded {
+            attacks.push(RangedAttack { item, range: weapon.range, shoot: true });
+        }
+    }
+    for (item, pack, item_range) in (entities, backpack, ranged).join() {
+        if pack.owner == owner {
+            attacks.push(RangedAttack {
+                item,
+                range: item_range.range,
+                shoot: ranged_weapons.get(item).is_some(),
+            });
+        }
+    }
+    attacks
+}
+
+/// The tiles the player's weapons could strike next turn: anywhere along a swing if they
+/// have the stamina for one, and otherwise just around where each weapon already is.
+fn weapon_danger(
+    map: &Map,
+    player: Point,
+    weapons: &[(Entity, Point)],
+    weapon_stats: &ReadStorage<WeaponStats>,
+    can_swing: bool,
+) -> Vec<Point> {
+    let mut danger: Vec<Point> = Vec::new();
+    for (weapon, weapon_pos) in weapons.iter() {
+        for (dx, dy) in NEIGHBOURS.iter() {
+            danger.push(Point::new(weapon_pos.x + dx, weapon_pos.y + dy));
+            if can_swing {
+                let reach = weapon_stats.get(*weapon).map_or(1, |w| w.reach);
+                for step in 1..=reach {
+                    danger.push(Point::new(player.x + dx * step, player.y + dy * step));
+                }
+            }
+        }
+    }
+    danger.retain(|p| p.x > 0 && p.x < map.width - 1 && p.y > 0 && p.y < map.height - 1);
+    danger
+}
+
+/// The tile on the far side of the player from their weapon, if there is room there.
+fn flank_point(map: &Map, player: Point, weapon: Point) -> Option<Point> {
+    let flank = Point::new(
+        player.x + (player.x - weapon.x).signum(),
+        player.y + (player.y - weapon.y).signum(),
+    );
+    if can_step(map, flank) {
+        Some(flank)
+    } else {
+        None
+    }
+}
+
+/// Picks somewhere nearby, and reachable, to wander off to.
+fn wander_destination(map: &Map, from: Point, rng: &mut RandomNumberGenerator) -> Option<Point> {
+    for _ in 0..10 {
+        let destination = Point::new(
+            from.x + rng.range(-WANDER_DISTANCE, WANDER_DISTANCE + 1),
+            from.y + rng.range(-WANDER_DISTANCE, WANDER_DISTANCE + 1),
+        );
+        if destination != from && can_step(map, destination) {
+            return Some(destination);
         }
     }
+    None
 }
@@ -0,0 +1,102 @@
+use super::{flow_map_system::FlowMap, Behaviour, Brain, Map, Position};
+use rltk::{Point, RandomNumberGenerator};
+use specs::prelude::*;
+
+/// Flow map distances are kept in hundredths of a tile.
+const TILE_COST: i32 = 100;
+
+/// Everything that makes a noise.
+#[derive(PartialEq, Copy, Clone)]
+pub enum NoiseKind {
+    /// Blows landing, or missing, in melee
+    Fighting,
+    /// A weapon banging into a wall
+    Clang,
+    /// A shot or a thrown weapon landing
+    Impact,
+    /// A trap going off
+    Trap,
+    /// An explosion
+    Blast,
+}
+
+impl NoiseKind {
+    /// How many tiles of open floor the noise carries across.
+    pub fn loudness(self) -> i32 {
+        match self {
+            NoiseKind::Fighting => 8,
+            NoiseKind::Clang => 12,
+            NoiseKind::Impact => 4,
+            NoiseKind::Trap => 8,
+            NoiseKind::Blast => 16,
+        }
+    }
+}
+
+struct Noise {
+    pos: Point,
+    loudness: i32,
+}
+
+/// Noises made since monsters last had a chance to hear them.
+#[derive(Default)]
+pub struct Noises {
+    events: Vec<Noise>,
+}
+
+impl Noises {
+    pub fn make(&mut self, pos: Point, kind: NoiseKind) {
+        self.events.push(Noise { pos, loudness: kind.loudness() });
+    }
+}
+
+/// Spreads each noise out across the map and lets the monsters within earshot hear it.
+/// Sound only travels across open floor, so a wall in the way muffles it by making it go
+/// the long way round. Monsters go to investigate what they heard, and a loud enough
+/// noise close by wakes sleeping ones.
+pub struct HearingSystem {}
+
+impl<'a> System<'a> for HearingSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        ReadExpect<'a, Map>,
+        WriteExpect<'a, Noises>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        WriteStorage<'a, Brain>,
+        ReadStorage<'a, Position>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (map, mut noises, mut rng, mut brains, positions) = data;
+
+        // Only the loudest noise from any one spot counts
+        let mut events = std::mem::take(&mut noises.events);
+        events.sort_by_key(|noise| (noise.pos.x, noise.pos.y, -noise.loudness));
+        events.dedup_by(|a, b| a.pos == b.pos);
+
+        for noise in events.iter() {
+            let start = map.xy_idx(noise.pos.x, noise.pos.y);
+            let spread = FlowMap::build(&map, &[(start, 0)]);
+
+            for (brain, pos) in (&mut brains, &positions).join() {
+                let heard = spread
+                    .distance(map.xy_idx(pos.x, pos.y))
+                    .map_or(0, |distance| noise.loudness - distance / TILE_COST);
+                if heard <= 0 {
+                    continue;
+                }
+
+                match brain.state {
+                    Behaviour::Hunt | Behaviour::Flee => {}
+                    // The closer and louder, the more likely it is to wake them
+                    Behaviour::Asleep if rng.roll_dice(1, 10) > heard => {}
+                    _ => {
+                        brain.state = Behaviour::Search;
+                        brain.last_seen = Some(noise.pos);
+                        brain.search_turns = brain.patience;
+                    }
+                }
+            }
+        }
+    }
+}
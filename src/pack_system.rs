@@ -0,0 +1,199 @@
+use super::{gamelog::GameLog, Behaviour, Brain, Map, PackMember, Position, RunState};
+use rltk::Point;
+use specs::prelude::*;
+use std::collections::HashMap;
+
+/// How far a follower strays from its leader before it heads back.
+const FOLLOW_DISTANCE: f32 = 3.0;
+
+/// How close to the leader every member has to be before the pack closes in.
+const GATHER_RADIUS: f32 = 4.0;
+
+/// What the pack planner wants a member to do this turn.
+#[derive(PartialEq, Copy, Clone)]
+pub enum PackOrder {
+    /// Keep up with the leader while the pack is going about its business
+    Follow(Point),
+    /// Fall back to the leader so the pack can regroup
+    Gather(Point),
+    /// Wait where it is for the rest of the pack to catch up
+    Hold,
+    /// Take up this position around the player
+    Surround(Point),
+}
+
+impl PackOrder {
+    /// Whether the order is part of going after the player.
+    pub fn is_hunting(&self) -> bool {
+        !matches!(self, PackOrder::Follow(_))
+    }
+}
+
+/// This turn's orders for every pack member.
+#[derive(Default)]
+pub struct PackPlans {
+    orders: HashMap<Entity, PackOrder>,
+}
+
+impl PackPlans {
+    pub fn order(&self, member: Entity) -> Option<PackOrder> {
+        self.orders.get(&member).copied()
+    }
+}
+
+/// Plans each pack's moves once a turn, for `MonsterAI` to carry out. A pack that has
+/// found the player waits until it is together and then surrounds them; a pack whose
+/// leader has died scatters.
+pub struct PackPlannerSystem {}
+
+impl<'a> System<'a> for PackPlannerSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, Map>,
+        ReadExpect<'a, Point>,
+        ReadExpect<'a, RunState>,
+        WriteExpect<'a, PackPlans>,
+        WriteExpect<'a, GameLog>,
+        WriteStorage<'a, PackMember>,
+        WriteStorage<'a, Brain>,
+        ReadStorage<'a, Position>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            map,
+            player_pos,
+            runstate,
+            mut plans,
+            mut log,
+            mut pack_members,
+            mut brains,
+            positions,
+        ) = data;
+
+        if *runstate != RunState::MonsterTurn {
+            return;
+        }
+        plans.orders.clear();
+
+        let mut packs: HashMap<Entity, Vec<(Entity, Point)>> = HashMap::new();
+        let mut leaderless: Vec<(Entity, Point)> = Vec::new();
+        for (entity, member, pos) in (&entities, &pack_members, &positions).join() {
+            let point = Point::new(pos.x, pos.y);
+            if entities.is_alive(member.leader) && positions.get(member.leader).is_some() {
+                packs
+                    .entry(member.leader)
+                    .or_default()
+                    .push((entity, point));
+            } else {
+                leaderless.push((entity, point));
+            }
+        }
+
+        // Without a leader the pack breaks and runs
+        let mut scattered = false;
+        for (member, point) in leaderless {
+            pack_members.remove(member);
+            if let Some(brain) = brains.get_mut(member) {
+                brain.state = Behaviour::Flee;
+            }
+            scattered |= map.visible_tiles[map.xy_idx(point.x, point.y)];
+        }
+        if scattered {
+            log.entries
+                .push("With its leader gone, the pack scatters!".to_string());
+        }
+
+        for (leader, members) in packs.iter() {
+            let leader_pos = positions
+                .get(*leader)
+                .map(|p| Point::new(p.x, p.y))
+                .unwrap();
+            let hunting = members.iter().any(|(member, _point)| {
+                brains
+                    .get(*member)
+                    .is_some_and(|b| b.state == Behaviour::Hunt)
+            });
+
+            if !hunting {
+                for (member, point) in members.iter().filter(|(member, _point)| member != leader) {
+                    if distance(*point, leader_pos) > FOLLOW_DISTANCE {
+                        plans.orders.insert(*member, PackOrder::Follow(leader_pos));
+                    }
+                }
+                continue;
+            }
+
+            // Regroup before closing in, unless someone is already in the thick of it
+            let together = members
+                .iter()
+                .all(|(_member, point)| distance(*point, leader_pos) <= GATHER_RADIUS);
+            if !together {
+                for (member, point) in members.iter() {
+                    if distance(*point, *player_pos) < 1.5 {
+                        continue;
+                    }
+                    let order = if member == leader || distance(*point, leader_pos) <= GATHER_RADIUS
+                    {
+                        PackOrder::Hold
+                    } else {
+                        PackOrder::Gather(leader_pos)
+                    };
+                    plans.orders.insert(*member, order);
+                }
+                continue;
+            }
+
+            // Share out the free tiles round the player, nearest member first
+            let mut slots: Vec<Point> = surrounding_tiles(&map, *player_pos, members);
+            let mut unassigned: Vec<(Entity, Point)> = members.clone();
+            while !slots.is_empty() && !unassigned.is_empty() {
+                let mut best: Option<(usize, usize, f32)> = None;
+                for (m, (_member, point)) in unassigned.iter().enumerate() {
+                    for (s, slot) in slots.iter().enumerate() {
+                        let d = distance(*point, *slot);
+                        if best.is_none_or(|(_m, _s, best_d)| d < best_d) {
+                            best = Some((m, s, d));
+                        }
+                    }
+                }
+                let (m, s, _d) = best.unwrap();
+                let (member, _point) = unassigned.remove(m);
+                plans
+                    .orders
+                    .insert(member, PackOrder::Surround(slots.remove(s)));
+            }
+        }
+    }
+}
+
+fn distance(a: Point, b: Point) -> f32 {
+    rltk::DistanceAlg::Pythagoras.distance2d(a, b)
+}
+
+/// The tiles around the player that a pack member could stand on, counting the ones
+/// members are already standing on.
+fn surrounding_tiles(map: &Map, player: Point, members: &[(Entity, Point)]) -> Vec<Point> {
+    let mut tiles: Vec<Point> = Vec::new();
+    for dy in -1..=1 {
+        for dx in -1..=1 {
+            let tile = Point::new(player.x + dx, player.y + dy);
+            if tile == player
+                || tile.x < 1
+                || tile.x > map.width - 2
+                || tile.y < 1
+                || tile.y > map.height - 2
+            {
+                continue;
+            }
+            let idx = map.xy_idx(tile.x, tile.y);
+            let occupied_by_pack = members.iter().any(|(_member, point)| *point == tile);
+            if map.is_walkable(idx) && (!map.blocked[idx] || occupied_by_pack) {
+                tiles.push(tile);
+            }
+        }
+    }
+    tiles
+}
@@ -1,16 +1,55 @@
-use super::{ParticleLifetime, Position, Renderable, Rltk};
-use rltk::RGB;
+use super::{Map, ParticleAnimation, ParticleLifetime, Position, Renderable, Rltk};
+use rltk::{Point, RGB};
 use specs::prelude::*;
 
-pub fn cull_dead_particles(ecs: &mut World, ctx: &Rltk) {
+/// How long a projectile takes to cross one tile.
+const PROJECTILE_MS_PER_TILE: f32 = 40.0;
+
+/// How long a blast takes to spread out by one more tile.
+const BLAST_MS_PER_RING: f32 = 60.0;
+
+/// How long each tile of a trail lingers.
+const TRAIL_MS: f32 = 250.0;
+
+/// Ages every particle: spawns the ones whose delay has run out, steps animations on by
+/// a frame, and removes particles that have expired.
+pub fn update_particles(ecs: &mut World, ctx: &Rltk) {
+    {
+        let mut particle_builder = ecs.fetch_mut::<ParticleBuilder>();
+        for request in particle_builder.requests.iter_mut() {
+            request.delay -= ctx.frame_time_ms;
+        }
+    }
+    let mut spawner = ParticleSpawnSystem {};
+    spawner.run_now(ecs);
+    ecs.maintain();
+
     let mut dead_particles: Vec<Entity> = Vec::new();
     {
         let mut particles = ecs.write_storage::<ParticleLifetime>();
+        let mut positions = ecs.write_storage::<Position>();
+        let mut renderables = ecs.write_storage::<Renderable>();
+        let map = ecs.fetch::<Map>();
         let entities = ecs.entities();
-        for (entity, mut particle) in (&entities, &mut particles).join() {
+        for (entity, particle, pos, render) in
+            (&entities, &mut particles, &mut positions, &mut renderables).join()
+        {
             particle.lifetime_ms -= ctx.frame_time_ms;
             if particle.lifetime_ms < 0.0 {
                 dead_particles.push(entity);
+                continue;
+            }
+
+            if let Some(animation) = &mut particle.animation {
+                animation.elapsed_ms += ctx.frame_time_ms;
+                let frame = (animation.elapsed_ms / animation.frame_ms) as usize;
+                render.glyph = animation.frames[frame % animation.frames.len()];
+
+                let seconds = animation.elapsed_ms / 1000.0;
+                let x = animation.origin.0 + (animation.velocity.0 * seconds) as i32;
+                let y = animation.origin.1 + (animation.velocity.1 * seconds) as i32;
+                pos.x = i32::min(map.width - 1, i32::max(0, x));
+                pos.y = i32::min(map.height - 1, i32::max(0, y));
             }
         }
     }
@@ -20,6 +59,44 @@ pub fn cull_dead_particles(ecs: &mut World, ctx: &Rltk) {
     }
 }
 
+/// Whether a blocking animation is still waiting to start or playing out.
+pub fn animation_playing(ecs: &World) -> bool {
+    let particle_builder = ecs.fetch::<ParticleBuilder>();
+    let particles = ecs.read_storage::<ParticleLifetime>();
+    particle_builder.requests.iter().any(|r| r.blocking) || particles.join().any(|p| p.blocking)
+}
+
+/// Cuts every blocking animation short, so the game can carry on straight away.
+pub fn skip_animations(ecs: &mut World) {
+    ecs.fetch_mut::<ParticleBuilder>()
+        .requests
+        .retain(|r| !r.blocking);
+
+    let blocking: Vec<Entity> = {
+        let particles = ecs.read_storage::<ParticleLifetime>();
+        let entities = ecs.entities();
+        (&entities, &particles)
+            .join()
+            .filter(|(_e, p)| p.blocking)
+            .map(|(e, _p)| e)
+            .collect()
+    };
+    for particle in blocking {
+        ecs.delete_entity(particle).expect("Particle will not die");
+    }
+}
+
+/// The glyph for something flying in a particular direction.
+pub fn projectile_glyph(dx: i32, dy: i32) -> rltk::FontCharType {
+    let glyph = match (dx.signum(), dy.signum()) {
+        (0, _) => '|',
+        (_, 0) => '-',
+        (x, y) if x == y => '\\',
+        _ => '/',
+    };
+    rltk::to_cp437(glyph)
+}
+
 struct ParticleRequest {
     x: i32,
     y: i32,
@@ -27,6 +104,9 @@ struct ParticleRequest {
     bg: RGB,
     glyph: rltk::FontCharType,
     lifetime: f32,
+    delay: f32,
+    animation: Option<ParticleAnimation>,
+    blocking: bool,
 }
 
 pub struct ParticleBuilder {
@@ -48,8 +128,129 @@ impl ParticleBuilder {
         glyph: rltk::FontCharType,
         lifetime: f32,
     ) {
-        self.requests
-            .push(ParticleRequest { x, y, fg, bg, glyph, lifetime });
+        self.requests.push(ParticleRequest {
+            x,
+            y,
+            fg,
+            bg,
+            glyph,
+            lifetime,
+            delay: 0.0,
+            animation: None,
+            blocking: false,
+        });
+    }
+
+    /// Flies a glyph along a path one tile at a time, starting after `delay` ms. Returns
+    /// when it arrives, so that whatever happens next can be queued up behind it.
+    pub fn projectile(
+        &mut self,
+        path: &[Point],
+        fg: RGB,
+        glyph: rltk::FontCharType,
+        delay: f32,
+    ) -> f32 {
+        let mut arrival = delay;
+        for point in path.iter() {
+            self.requests.push(ParticleRequest {
+                x: point.x,
+                y: point.y,
+                fg,
+                bg: RGB::named(rltk::BLACK),
+                glyph,
+                lifetime: PROJECTILE_MS_PER_TILE,
+                delay: arrival,
+                animation: None,
+                blocking: true,
+            });
+            arrival += PROJECTILE_MS_PER_TILE;
+        }
+        arrival
+    }
+
+    /// Spreads an explosion out from `centre` across `tiles` a ring at a time, starting
+    /// after `delay` ms. Returns when the last ring goes up.
+    pub fn blast(&mut self, centre: Point, tiles: &[Point], fg: RGB, delay: f32) -> f32 {
+        let frames: Vec<rltk::FontCharType> =
+            ['*', '+', '∙'].iter().map(|c| rltk::to_cp437(*c)).collect();
+        let mut finished = delay;
+        for point in tiles.iter() {
+            let ring = i32::max(i32::abs(point.x - centre.x), i32::abs(point.y - centre.y));
+            let start = delay + ring as f32 * BLAST_MS_PER_RING;
+            self.requests.push(ParticleRequest {
+                x: point.x,
+                y: point.y,
+                fg,
+                bg: RGB::named(rltk::BLACK),
+                glyph: frames[0],
+                lifetime: BLAST_MS_PER_RING * 3.0,
+                delay: start,
+                animation: Some(ParticleAnimation {
+                    frames: frames.clone(),
+                    frame_ms: BLAST_MS_PER_RING,
+                    velocity: (0.0, 0.0),
+                    origin: (point.x, point.y),
+                    elapsed_ms: 0.0,
+                }),
+                blocking: true,
+            });
+            finished = f32::max(finished, start + BLAST_MS_PER_RING * 3.0);
+        }
+        finished
+    }
+
+    /// Leaves a fading trail behind something moving quickly along a path, such as a
+    /// weapon being swung. Trails don't hold up the turn.
+    pub fn trail(&mut self, path: &[Point], fg: RGB, glyph: rltk::FontCharType) {
+        let frames = vec![glyph, rltk::to_cp437('∙'), rltk::to_cp437('·')];
+        for (i, point) in path.iter().enumerate() {
+            self.requests.push(ParticleRequest {
+                x: point.x,
+                y: point.y,
+                fg,
+                bg: RGB::named(rltk::BLACK),
+                glyph,
+                lifetime: TRAIL_MS,
+                delay: i as f32 * PROJECTILE_MS_PER_TILE,
+                animation: Some(ParticleAnimation {
+                    frames: frames.clone(),
+                    frame_ms: TRAIL_MS / 3.0,
+                    velocity: (0.0, 0.0),
+                    origin: (point.x, point.y),
+                    elapsed_ms: 0.0,
+                }),
+                blocking: false,
+            });
+        }
+    }
+
+    /// Sends a glyph drifting off across the map, like a spark or a splash.
+    pub fn drift(
+        &mut self,
+        x: i32,
+        y: i32,
+        fg: RGB,
+        glyph: rltk::FontCharType,
+        velocity: (f32, f32),
+        lifetime: f32,
+    ) {
+        self.requests.push(ParticleRequest {
+            x,
+            y,
+            fg,
+            bg: RGB::named(rltk::BLACK),
+            glyph,
+            lifetime,
+            delay: 0.0,
+            animation: Some(ParticleAnimation {
+                frames: vec![glyph],
+                frame_ms: lifetime,
+                velocity,
+                origin: (x, y),
+                elapsed_ms: 0.0,
+            }),
+            blocking: false,
+        });
     }
 }
 
@@ -68,7 +269,8 @@ impl<'a> System<'a> for ParticleSpawnSystem {
     fn run(&mut self, data: Self::SystemData) {
         let (entities, mut positions, mut renderables, mut particles, mut particle_builder) = data;
 
-        for new_particle in particle_builder.requests.iter() {
+        // Delayed particles wait their turn in the queue
+        for new_particle in particle_builder.requests.iter().filter(|r| r.delay <= 0.0) {
             let p = entities.create();
             positions
                 .insert(p, Position { x: new_particle.x, y: new_particle.y })
@@ -85,10 +287,17 @@ impl<'a> System<'a> for ParticleSpawnSystem {
                 )
                 .expect("Unable to insert renderable");
             particles
-                .insert(p, ParticleLifetime { lifetime_ms: new_particle.lifetime })
+                .insert(
+                    p,
+                    ParticleLifetime {
+                        lifetime_ms: new_particle.lifetime,
+                        animation: new_particle.animation.clone(),
+                        blocking: new_particle.blocking,
+                    },
+                )
                 .expect("Unable to insert lifetime");
         }
 
-        particle_builder.requests.clear();
+        particle_builder.requests.retain(|r| r.delay > 0.0);
     }
 }
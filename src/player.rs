@@ -1,12 +1,72 @@
 use super::{
-    gamelog::GameLog, CombatStats, EntityMoved, Item, Map, Monster, Player, Position, RunState,
-    State, TileType, Viewshed, WantsToMelee, WantsToPickupItem, WeaponStats,
+    gamelog::GameLog,
+    noise_system::{NoiseKind, Noises},
+    particle_system::ParticleBuilder,
+    stamina_system::{spend_stamina, StaminaAction},
+    status_effect_system::{is_affected, BREAK_FREE_DC},
+    visibility_system::PASSIVE_SPOT_DC,
+    Attributes, CombatStats, Dodging, EntityMoved, EntryTrigger, EquipmentSlot, Equipped,
+    Exhausted, Hidden, Item, Map, Monster, Name, Player, Position, RangedWeapon, Renderable,
+    RunState, State, StatusEffects, StatusKind, TileType, Viewshed, WantsToMelee,
+    WantsToPickupItem, WeaponStats,
 };
-use rltk::{Point, Rltk, VirtualKeyCode};
+use rltk::{Point, RandomNumberGenerator, Rltk, VirtualKeyCode, RGB};
 use specs::prelude::*;
 use std::cmp::{max, min};
 
+/// A confused player only goes where they meant to half of the time.
+fn confused_direction(ecs: &World, delta_x: i32, delta_y: i32) -> (i32, i32) {
+    let player_entity = ecs.fetch::<Entity>();
+    let statuses = ecs.read_storage::<StatusEffects>();
+    if !is_affected(&statuses, *player_entity, StatusKind::Confusion) {
+        return (delta_x, delta_y);
+    }
+
+    let mut rng = ecs.write_resource::<RandomNumberGenerator>();
+    if rng.roll_dice(1, 2) == 1 {
+        return (delta_x, delta_y);
+    }
+    let mut log = ecs.write_resource::<GameLog>();
+    log.entries
+        .push("You stumble about in confusion.".to_string());
+    loop {
+        let (dx, dy) = (rng.range(-1, 2), rng.range(-1, 2));
+        if dx != 0 || dy != 0 {
+            return (dx, dy);
+        }
+    }
+}
+
+/// An immobilised player spends their move trying to break free instead.
+fn held_in_place(ecs: &World) -> bool {
+    let player_entity = ecs.fetch::<Entity>();
+    let mut statuses = ecs.write_storage::<StatusEffects>();
+    let statuses = match statuses.get_mut(*player_entity) {
+        Some(statuses) if statuses.has(StatusKind::Immobilised) => statuses,
+        _ => return false,
+    };
+
+    let attributes = ecs.read_storage::<Attributes>();
+    let mut rng = ecs.write_resource::<RandomNumberGenerator>();
+    let mut log = ecs.write_resource::<GameLog>();
+    let freed = attributes
+        .get(*player_entity)
+        .is_some_and(|a| a.strength.check(&mut rng, BREAK_FREE_DC));
+    if freed {
+        statuses.remove_effect(StatusKind::Immobilised);
+        log.entries.push("You wrench yourself free!".to_string());
+    } else {
+        log.entries
+            .push("You struggle, but can't break free.".to_string());
+    }
+    true
+}
+
 pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
+    if held_in_place(ecs) {
+        return;
+    }
+    let (delta_x, delta_y) = confused_direction(ecs, delta_x, delta_y);
     let mut player_x = 0;
     let mut player_y = 0;
 
@@ -138,12 +198,45 @@ pub fn try_move_weapon_w_player(
     return false;
 }
 
+/// Swings the weapon out from the player in the given direction, as far as its reach allows,
+/// leaving a trail through the tiles it sweeps across.
 pub fn try_move_weapon_simple(delta_x: i32, delta_y: i32, ecs: &mut World) {
+    let before = player_weapon_point(ecs);
+    swing_weapon(delta_x, delta_y, ecs);
+    let after = player_weapon_point(ecs);
+
+    if let (Some(before), Some(after)) = (before, after) {
+        if before != after {
+            let renderables = ecs.read_storage::<Renderable>();
+            let weapon = renderables.get(after.0);
+            let swept: Vec<Point> = rltk::line2d(rltk::LineAlg::Bresenham, before.1, after.1)
+                .into_iter()
+                .filter(|p| *p != after.1)
+                .collect();
+            if let Some(weapon) = weapon {
+                let mut particle_builder = ecs.fetch_mut::<ParticleBuilder>();
+                particle_builder.trail(&swept, weapon.fg, weapon.glyph);
+            }
+        }
+    }
+}
+
+/// Where the player's weapon is, if they have one out.
+fn player_weapon_point(ecs: &World) -> Option<(Entity, Point)> {
     let player_entity = ecs.fetch::<Entity>();
+    let positions = ecs.read_storage::<Position>();
+    let weapon_stats = ecs.read_storage::<WeaponStats>();
+    let entities = ecs.entities();
+    (&entities, &weapon_stats, &positions)
+        .join()
+        .find(|(_e, stats, _pos)| stats.owner == *player_entity)
+        .map(|(e, _stats, pos)| (e, Point::new(pos.x, pos.y)))
+}
 
-    // only used if player_moved for detection
+fn swing_weapon(delta_x: i32, delta_y: i32, ecs: &mut World) {
+    let (delta_x, delta_y) = confused_direction(ecs, delta_x, delta_y);
+    let player_entity = ecs.fetch::<Entity>();
     let player_pos = ecs.fetch::<Point>();
-    let (player_x, player_y) = (player_pos.x + delta_x, player_pos.y + delta_y);
 
     let mut positions = ecs.write_storage::<Position>();
     let weapon_stats = ecs.read_storage::<WeaponStats>();
@@ -152,33 +245,50 @@ pub fn try_move_weapon_simple(delta_x: i32, delta_y: i32, ecs: &mut World) {
     let map = ecs.fetch::<Map>();
     let mut wants_to_melee = ecs.write_storage::<WantsToMelee>();
 
-    for (entity, _weaponstat, pos) in (&entities, &weapon_stats, &mut positions)
+    for (entity, weaponstat, pos) in (&entities, &weapon_stats, &mut positions)
         .join()
         .filter(|item| item.1.owner == *player_entity)
     {
-        if player_x < 0 || player_x > map.width - 1 || player_y < 0 || player_y > map.height - 1 {
-            return;
-        }
+        for step in 1..=weaponstat.reach {
+            let (x, y) = (player_pos.x + delta_x * step, player_pos.y + delta_y * step);
+            if x < 0 || x > map.width - 1 || y < 0 || y > map.height - 1 {
+                return;
+            }
 
-        let destination_idx = map.xy_idx(player_x, player_y);
+            let destination_idx = map.xy_idx(x, y);
 
-        for potential_target in map.tile_content[destination_idx].iter() {
-            if *potential_target == *player_entity {
-                return;
+            for potential_target in map.tile_content[destination_idx].iter() {
+                if *potential_target == *player_entity {
+                    return;
+                }
+                if *potential_target == entity {
+                    continue;
+                }
+
+                let target = combat_stats.get(*potential_target);
+                if let Some(_target) = target {
+                    wants_to_melee
+                        .insert(entity, WantsToMelee { target: *potential_target })
+                        .expect("Add target failed");
+                    return;
+                }
             }
 
-            let target = combat_stats.get(*potential_target);
-            if let Some(_target) = target {
-                wants_to_melee
-                    .insert(entity, WantsToMelee { target: *potential_target })
-                    .expect("Add target failed");
+            let is_own_tile = x == pos.x && y == pos.y;
+            if map.blocked[destination_idx] && !is_own_tile {
+                // Banging into a wall makes a racket, which might draw monsters over
+                if !map.is_walkable(destination_idx) {
+                    let clang_at = Point::new(x - delta_x, y - delta_y);
+                    ecs.fetch_mut::<Noises>().make(clang_at, NoiseKind::Clang);
+                    ecs.fetch_mut::<GameLog>()
+                        .entries
+                        .push("Your weapon clangs against the wall.".to_string());
+                }
                 return;
             }
-        }
 
-        if !map.blocked[destination_idx] {
-            pos.x = min(79, max(0, player_x));
-            pos.y = min(49, max(0, player_y));
+            pos.x = min(79, max(0, x));
+            pos.y = min(49, max(0, y));
         }
     }
 }
@@ -259,6 +369,17 @@ fn skip_turn(ecs: &mut World) -> RunState {
 }
 
 pub fn player_input(gs: &mut State, ctx: &mut Rltk) -> RunState {
+    // Stunned players lose their turn
+    {
+        let player_entity = gs.ecs.fetch::<Entity>();
+        let statuses = gs.ecs.read_storage::<StatusEffects>();
+        if is_affected(&statuses, *player_entity, StatusKind::Stun) {
+            let mut log = gs.ecs.write_resource::<GameLog>();
+            log.entries.push("You are stunned!".to_string());
+            return RunState::PlayerTurn;
+        }
+    }
+
     // Player movement
     match ctx.key {
         None => return RunState::AwaitingInput, // Nothing happened
@@ -317,11 +438,19 @@ pub fn player_input(gs: &mut State, ctx: &mut Rltk) -> RunState {
             VirtualKeyCode::S => return RunState::Dodge,
             VirtualKeyCode::Z => return RunState::Dodge,
 
+            // Dealing with traps
+            VirtualKeyCode::X => search(&mut gs.ecs),
+            VirtualKeyCode::T => return RunState::Disarm,
+
+            // Shooting
+            VirtualKeyCode::F => return fire(&mut gs.ecs),
+
             VirtualKeyCode::F1 => {
                 let mut gamelog = gs.ecs.fetch_mut::<GameLog>();
-                gamelog
-                    .entries
-                    .push("'A' to move weapon, 'Z' to dodge".to_string());
+                gamelog.entries.push(
+                    "'A' to move weapon, 'Z' to dodge, 'X' to search, 'T' to disarm, 'F' to fire"
+                        .to_string(),
+                );
                 return RunState::AwaitingInput;
             }
 
@@ -334,17 +463,168 @@ pub fn player_input(gs: &mut State, ctx: &mut Rltk) -> RunState {
     RunState::PlayerTurn
 }
 
-fn player_use_stamina(ecs: &mut World, amount: i32) -> bool {
+/// Takes aim with whatever ranged weapon the player has equipped.
+fn fire(ecs: &mut World) -> RunState {
+    let player_entity = ecs.fetch::<Entity>();
+    let equipped = ecs.read_storage::<Equipped>();
+    let ranged_weapons = ecs.read_storage::<RangedWeapon>();
+    let entities = ecs.entities();
+
+    let launcher = (&entities, &equipped, &ranged_weapons)
+        .join()
+        .find(|(_e, eq, _rw)| eq.owner == *player_entity && eq.slot == EquipmentSlot::Ranged);
+    match launcher {
+        Some((item, _eq, weapon)) => RunState::ShowTargeting { range: weapon.range, item },
+        None => {
+            let mut log = ecs.fetch_mut::<GameLog>();
+            log.entries.push("You have nothing to fire.".to_string());
+            RunState::AwaitingInput
+        }
+    }
+}
+
+/// How far around the player a search reaches.
+const SEARCH_RADIUS: f32 = 3.0;
+
+/// How much easier it is to find a trap by looking for it than by walking past.
+const SEARCH_BONUS: i32 = 8;
+
+/// The dexterity check needed to disarm a trap.
+const DISARM_DC: i32 = 12;
+
+/// Spends a turn looking carefully for hidden traps nearby.
+fn search(ecs: &mut World) {
+    let player_entity = ecs.fetch::<Entity>();
+    let player_pos = ecs.fetch::<Point>();
+    let viewsheds = ecs.read_storage::<Viewshed>();
+    let attributes = ecs.read_storage::<Attributes>();
+    let positions = ecs.read_storage::<Position>();
+    let names = ecs.read_storage::<Name>();
+    let mut hidden = ecs.write_storage::<Hidden>();
+    let mut rng = ecs.write_resource::<RandomNumberGenerator>();
+    let mut log = ecs.write_resource::<GameLog>();
+    let entities = ecs.entities();
+
+    log.entries.push("You search the area.".to_string());
+    let viewshed = viewsheds.get(*player_entity).unwrap();
+    let mut found: Vec<Entity> = Vec::new();
+    for (entity, pos, _hidden) in (&entities, &positions, &hidden).join() {
+        let point = Point::new(pos.x, pos.y);
+        let distance = rltk::DistanceAlg::Pythagoras.distance2d(point, *player_pos);
+        if distance > SEARCH_RADIUS || !viewshed.visible_tiles.contains(&point) {
+            continue;
+        }
+        let spotted = attributes
+            .get(*player_entity)
+            .is_some_and(|a| a.dexterity.check(&mut rng, PASSIVE_SPOT_DC - SEARCH_BONUS));
+        if spotted {
+            found.push(entity);
+        }
+    }
+
+    for entity in found {
+        if let Some(name) = names.get(entity) {
+            log.entries.push(format!("You find a {}!", &name.name));
+        }
+        hidden.remove(entity);
+    }
+}
+
+/// Picks the direction of a trap to disarm.
+pub fn player_disarm_input(gs: &mut State, ctx: &mut Rltk) -> RunState {
+    let (dx, dy) = match ctx.key {
+        None => return RunState::Disarm, // Nothing happened
+        Some(key) => match key {
+            VirtualKeyCode::Left | VirtualKeyCode::Numpad4 | VirtualKeyCode::H => (-1, 0),
+            VirtualKeyCode::Right | VirtualKeyCode::Numpad6 | VirtualKeyCode::L => (1, 0),
+            VirtualKeyCode::Up | VirtualKeyCode::Numpad8 | VirtualKeyCode::K => (0, -1),
+            VirtualKeyCode::Down | VirtualKeyCode::Numpad2 | VirtualKeyCode::J => (0, 1),
+
+            // Diagonals
+            VirtualKeyCode::Numpad9 | VirtualKeyCode::U => (1, -1),
+            VirtualKeyCode::Numpad7 | VirtualKeyCode::Y => (-1, -1),
+            VirtualKeyCode::Numpad3 | VirtualKeyCode::N => (1, 1),
+            VirtualKeyCode::Numpad1 | VirtualKeyCode::B => (-1, 1),
+
+            // Escape disarm mode
+            VirtualKeyCode::Escape => return RunState::AwaitingInput,
+            _ => return RunState::Disarm,
+        },
+    };
+
+    try_disarm(&mut gs.ecs, dx, dy)
+}
+
+/// Tries to take apart a trap the player knows about next to them.
+fn try_disarm(ecs: &mut World, delta_x: i32, delta_y: i32) -> RunState {
+    let player_pos = *ecs.fetch::<Point>();
+    let player_entity = ecs.fetch::<Entity>();
+    let (x, y) = (player_pos.x + delta_x, player_pos.y + delta_y);
+
+    let trap = {
+        let positions = ecs.read_storage::<Position>();
+        let triggers = ecs.read_storage::<EntryTrigger>();
+        let hidden = ecs.read_storage::<Hidden>();
+        let entities = ecs.entities();
+        (&entities, &positions, &triggers, !&hidden)
+            .join()
+            .find(|(_entity, pos, _trigger, _hidden)| pos.x == x && pos.y == y)
+            .map(|(entity, _pos, _trigger, _hidden)| entity)
+    };
+
+    let mut log = ecs.write_resource::<GameLog>();
+    let trap = match trap {
+        None => {
+            log.entries
+                .push("You don't know of a trap there.".to_string());
+            return RunState::AwaitingInput;
+        }
+        Some(trap) => trap,
+    };
+
+    let name = ecs
+        .read_storage::<Name>()
+        .get(trap)
+        .map_or("trap".to_string(), |n| n.name.clone());
+    let disarmed = {
+        let attributes = ecs.read_storage::<Attributes>();
+        let mut rng = ecs.write_resource::<RandomNumberGenerator>();
+        attributes
+            .get(*player_entity)
+            .is_some_and(|a| a.dexterity.check(&mut rng, DISARM_DC))
+    };
+    if disarmed {
+        log.entries.push(format!("You disarm the {}.", name));
+        ecs.entities().delete(trap).expect("Unable to delete trap");
+    } else {
+        log.entries
+            .push(format!("You fail to disarm the {}.", name));
+    }
+
+    RunState::PlayerTurn
+}
+
+fn player_use_stamina(ecs: &mut World, action: StaminaAction) -> bool {
     let player_entity = ecs.fetch::<Entity>();
     let mut combat_stats = ecs.write_storage::<CombatStats>();
+    let mut exhausted = ecs.write_storage::<Exhausted>();
     let player_stats = combat_stats.get_mut(*player_entity).unwrap();
 
-    if player_stats.stamina < amount {
-        false
+    if spend_stamina(*player_entity, player_stats, &mut exhausted, action) {
+        return true;
+    }
+
+    let mut gamelog = ecs.fetch_mut::<GameLog>();
+    if exhausted.get(*player_entity).is_some() {
+        gamelog
+            .entries
+            .push("You are too exhausted, rest to get your breath back.".to_string());
     } else {
-        player_stats.stamina = player_stats.stamina - amount;
-        true
+        gamelog
+            .entries
+            .push("You don't have the stamina for that.".to_string());
     }
+    false
 }
 
 ///
@@ -356,50 +636,50 @@ pub fn player_weapon_input(gs: &mut State, ctx: &mut Rltk) -> RunState {
         None => return RunState::MoveWeapon, // Nothing happened
         Some(key) => match key {
             VirtualKeyCode::Left | VirtualKeyCode::Numpad4 | VirtualKeyCode::H => {
-                if player_use_stamina(&mut gs.ecs, 1) {
+                if player_use_stamina(&mut gs.ecs, StaminaAction::WeaponSwing) {
                     try_move_weapon_simple(-1, 0, &mut gs.ecs);
                 }
             }
 
             VirtualKeyCode::Right | VirtualKeyCode::Numpad6 | VirtualKeyCode::L => {
-                if player_use_stamina(&mut gs.ecs, 1) {
+                if player_use_stamina(&mut gs.ecs, StaminaAction::WeaponSwing) {
                     try_move_weapon_simple(1, 0, &mut gs.ecs);
                 }
             }
 
             VirtualKeyCode::Up | VirtualKeyCode::Numpad8 | VirtualKeyCode::K => {
-                if player_use_stamina(&mut gs.ecs, 1) {
+                if player_use_stamina(&mut gs.ecs, StaminaAction::WeaponSwing) {
                     try_move_weapon_simple(0, -1, &mut gs.ecs);
                 }
             }
 
             VirtualKeyCode::Down | VirtualKeyCode::Numpad2 | VirtualKeyCode::J => {
-                if player_use_stamina(&mut gs.ecs, 1) {
+                if player_use_stamina(&mut gs.ecs, StaminaAction::WeaponSwing) {
                     try_move_weapon_simple(0, 1, &mut gs.ecs);
                 }
             }
 
             // Diagonals
             VirtualKeyCode::Numpad9 | VirtualKeyCode::U => {
-                if player_use_stamina(&mut gs.ecs, 1) {
+                if player_use_stamina(&mut gs.ecs, StaminaAction::WeaponSwing) {
                     try_move_weapon_simple(1, -1, &mut gs.ecs);
                 }
             }
 
             VirtualKeyCode::Numpad7 | VirtualKeyCode::Y => {
-                if player_use_stamina(&mut gs.ecs, 1) {
+                if player_use_stamina(&mut gs.ecs, StaminaAction::WeaponSwing) {
                     try_move_weapon_simple(-1, -1, &mut gs.ecs);
                 }
             }
 
             VirtualKeyCode::Numpad3 | VirtualKeyCode::N => {
-                if player_use_stamina(&mut gs.ecs, 1) {
+                if player_use_stamina(&mut gs.ecs, StaminaAction::WeaponSwing) {
                     try_move_weapon_simple(1, 1, &mut gs.ecs);
                 }
             }
 
             VirtualKeyCode::Numpad1 | VirtualKeyCode::B => {
-                if player_use_stamina(&mut gs.ecs, 1) {
+                if player_use_stamina(&mut gs.ecs, StaminaAction::WeaponSwing) {
                     try_move_weapon_simple(-1, 1, &mut gs.ecs);
                 }
             }
@@ -431,28 +711,28 @@ pub fn player_shield_input(gs: &mut State, ctx: &mut Rltk) -> RunState {
         None => return RunState::MoveShield, // Nothing happened
         Some(key) => match key {
             VirtualKeyCode::Left | VirtualKeyCode::Numpad4 | VirtualKeyCode::H => {
-                if player_use_stamina(&mut gs.ecs, 1) {
+                if player_use_stamina(&mut gs.ecs, StaminaAction::ShieldMove) {
                     try_move_player(-1, 0, &mut gs.ecs);
                     try_move_player(-1, 0, &mut gs.ecs);
                 }
             }
 
             VirtualKeyCode::Right | VirtualKeyCode::Numpad6 | VirtualKeyCode::L => {
-                if player_use_stamina(&mut gs.ecs, 1) {
+                if player_use_stamina(&mut gs.ecs, StaminaAction::ShieldMove) {
                     try_move_player(1, 0, &mut gs.ecs);
                     try_move_player(1, 0, &mut gs.ecs);
                 }
             }
 
             VirtualKeyCode::Up | VirtualKeyCode::Numpad8 | VirtualKeyCode::K => {
-                if player_use_stamina(&mut gs.ecs, 1) {
+                if player_use_stamina(&mut gs.ecs, StaminaAction::ShieldMove) {
                     try_move_player(0, -1, &mut gs.ecs);
                     try_move_player(0, -1, &mut gs.ecs);
                 }
             }
 
             VirtualKeyCode::Down | VirtualKeyCode::Numpad2 | VirtualKeyCode::J => {
-                if player_use_stamina(&mut gs.ecs, 1) {
+                if player_use_stamina(&mut gs.ecs, StaminaAction::ShieldMove) {
                     try_move_player(0, 1, &mut gs.ecs);
                     try_move_player(0, 1, &mut gs.ecs);
                 }
@@ -460,28 +740,28 @@ pub fn player_shield_input(gs: &mut State, ctx: &mut Rltk) -> RunState {
 
             // Diagonals
             VirtualKeyCode::Numpad9 | VirtualKeyCode::U => {
-                if player_use_stamina(&mut gs.ecs, 1) {
+                if player_use_stamina(&mut gs.ecs, StaminaAction::ShieldMove) {
                     try_move_player(1, -1, &mut gs.ecs);
                     try_move_player(1, -1, &mut gs.ecs);
                 }
             }
 
             VirtualKeyCode::Numpad7 | VirtualKeyCode::Y => {
-                if player_use_stamina(&mut gs.ecs, 1) {
+                if player_use_stamina(&mut gs.ecs, StaminaAction::ShieldMove) {
                     try_move_player(-1, -1, &mut gs.ecs);
                     try_move_player(-1, -1, &mut gs.ecs);
                 }
             }
 
             VirtualKeyCode::Numpad3 | VirtualKeyCode::N => {
-                if player_use_stamina(&mut gs.ecs, 1) {
+                if player_use_stamina(&mut gs.ecs, StaminaAction::ShieldMove) {
                     try_move_player(1, 1, &mut gs.ecs);
                     try_move_player(1, 1, &mut gs.ecs);
                 }
             }
 
             VirtualKeyCode::Numpad1 | VirtualKeyCode::B => {
-                if player_use_stamina(&mut gs.ecs, 1) {
+                if player_use_stamina(&mut gs.ecs, StaminaAction::ShieldMove) {
                     try_move_player(-1, 1, &mut gs.ecs);
                     try_move_player(-1, 1, &mut gs.ecs);
                 }
@@ -579,16 +859,47 @@ pub fn player_dodge_input(gs: &mut State, ctx: &mut Rltk) -> RunState {
         },
     }
 
-    let mut did_dodge = false;
-    if is_dodge && player_use_stamina(&mut gs.ecs, 1) {
-        did_dodge = true;
+    if is_dodge && player_use_stamina(&mut gs.ecs, StaminaAction::Dodge) {
+        let from = *gs.ecs.fetch::<Point>();
         try_move_player(dx, dy, &mut gs.ecs);
+        let midway = *gs.ecs.fetch::<Point>();
         try_move_player(dx, dy, &mut gs.ecs);
-    }
-    if did_dodge {
-        let mut gamelog = gs.ecs.fetch_mut::<GameLog>();
-        gamelog.entries.push("You dodge!".to_string());
+
+        start_dodge(&mut gs.ecs, from, midway, dx, dy);
     }
 
     RunState::PlayerTurn
 }
+
+/// Opens the player's evasion window for the coming monster turn, and leaves an
+/// afterimage behind.
+fn start_dodge(ecs: &mut World, from: Point, midway: Point, dx: i32, dy: i32) {
+    let player_entity = ecs.fetch::<Entity>();
+    let mut dodging = ecs.write_storage::<Dodging>();
+    dodging
+        .insert(*player_entity, Dodging { from_x: from.x, from_y: from.y, dx, dy })
+        .expect("Unable to insert dodge");
+
+    let mut particle_builder = ecs.fetch_mut::<ParticleBuilder>();
+    particle_builder.request(
+        from.x,
+        from.y,
+        RGB::named(rltk::DARK_CYAN),
+        RGB::named(rltk::BLACK),
+        rltk::to_cp437('@'),
+        300.0,
+    );
+    if midway != from {
+        particle_builder.request(
+            midway.x,
+            midway.y,
+            RGB::named(rltk::CYAN),
+            RGB::named(rltk::BLACK),
+            rltk::to_cp437('@'),
+            150.0,
+        );
+    }
+
+    let mut gamelog = ecs.fetch_mut::<GameLog>();
+    gamelog.entries.push("You dodge!".to_string());
+}
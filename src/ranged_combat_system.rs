@@ -0,0 +1,221 @@
+use super::{
+    gamelog::GameLog,
+    melee_combat_system::provoke,
+    noise_system::{NoiseKind, Noises},
+    particle_system::{projectile_glyph, ParticleBuilder},
+    spawner, Allegiance, Ammo, AmmoKind, Attributes, CombatStats, InBackpack, Map, Name, Position,
+    RangedWeapon, SufferDamage, WantsToShoot, WeaponStats,
+};
+use rltk::{BaseMap, Point, RandomNumberGenerator};
+use specs::prelude::*;
+
+/// What a shooter has to roll on a d20 to hit a target with no dexterity bonus.
+const BASE_TO_HIT: i32 = 10;
+
+/// The tiles a projectile flies through on its way from `from` towards `to`. It stops short
+/// of the first wall, or on the first tile with something blocking it, unless that tile is
+/// in `pass_through`. The last tile is where the projectile ends up.
+pub fn line_of_fire(map: &Map, from: Point, to: Point, pass_through: &[Point]) -> Vec<Point> {
+    let mut path: Vec<Point> = Vec::new();
+    for point in rltk::line2d(rltk::LineAlg::Bresenham, from, to) {
+        if point == from {
+            continue;
+        }
+        if point.x < 0 || point.x > map.width - 1 || point.y < 0 || point.y > map.height - 1 {
+            break;
+        }
+        let idx = map.xy_idx(point.x, point.y);
+        if map.is_opaque(idx) {
+            break;
+        }
+        path.push(point);
+        if map.blocked[idx] && !pass_through.contains(&point) {
+            break;
+        }
+    }
+    path
+}
+
+/// The points an entity's own weapons occupy, which it can shoot past.
+pub fn own_weapon_points(
+    owner: Entity,
+    weapon_stats: &ReadStorage<WeaponStats>,
+    positions: &ReadStorage<Position>,
+) -> Vec<Point> {
+    (weapon_stats, positions)
+        .join()
+        .filter(|(stats, _pos)| stats.owner == owner)
+        .map(|(_stats, pos)| Point::new(pos.x, pos.y))
+        .collect()
+}
+
+pub struct RangedCombatSystem {}
+
+impl<'a> System<'a> for RangedCombatSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, GameLog>,
+        ReadExpect<'a, Map>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        WriteExpect<'a, ParticleBuilder>,
+        Read<'a, LazyUpdate>,
+        WriteStorage<'a, WantsToShoot>,
+        ReadStorage<'a, RangedWeapon>,
+        WriteStorage<'a, Ammo>,
+        ReadStorage<'a, InBackpack>,
+        ReadStorage<'a, Position>,
+        WriteStorage<'a, Name>,
+        ReadStorage<'a, CombatStats>,
+        ReadStorage<'a, Attributes>,
+        ReadStorage<'a, WeaponStats>,
+        WriteStorage<'a, SufferDamage>,
+        WriteExpect<'a, Noises>,
+        WriteStorage<'a, Allegiance>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            player_entity,
+            mut log,
+            map,
+            mut rng,
+            mut particle_builder,
+            lazy,
+            mut wants_shoot,
+            ranged_weapons,
+            mut ammo,
+            backpack,
+            positions,
+            mut names,
+            combat_stats,
+            attributes,
+            weapon_stats,
+            mut inflict_damage,
+            mut noises,
+            mut allegiances,
+        ) = data;
+
+        for (shooter, wants) in (&entities, &wants_shoot).join() {
+            let weapon = match ranged_weapons.get(wants.weapon) {
+                None => continue,
+                Some(weapon) => weapon,
+            };
+            let shooter_name = names
+                .get(shooter)
+                .map_or("Something".to_string(), |n| n.name.clone());
+
+            // Thrown weapons are their own ammunition, anything else needs some in the pack
+            let quiver = if ammo.get(wants.weapon).is_some() {
+                Some(wants.weapon)
+            } else {
+                (&entities, &ammo, &backpack)
+                    .join()
+                    .find(|(_e, a, pack)| pack.owner == shooter && a.kind == weapon.ammo)
+                    .map(|(e, _a, _pack)| e)
+            };
+            let quiver = match quiver {
+                None => {
+                    if shooter == *player_entity {
+                        log.entries.push("You are out of ammunition.".to_string());
+                    }
+                    continue;
+                }
+                Some(quiver) => quiver,
+            };
+            let bundle = ammo.get_mut(quiver).unwrap();
+            bundle.count -= 1;
+            if bundle.count < 1 {
+                entities.delete(quiver).expect("Unable to delete ammo");
+            } else if let Some(name) = names.get_mut(quiver) {
+                name.name = bundle.kind.bundle_name(bundle.count);
+            }
+
+            // Fly along the line of fire until something gets in the way
+            let shooter_pos = positions.get(shooter).unwrap();
+            let from = Point::new(shooter_pos.x, shooter_pos.y);
+            let pass_through = own_weapon_points(shooter, &weapon_stats, &positions);
+            let path = line_of_fire(&map, from, wants.target, &pass_through);
+            let landing = match path.last() {
+                None => from,
+                Some(landing) => *landing,
+            };
+            particle_builder.projectile(
+                &path,
+                rltk::RGB::named(rltk::WHEAT),
+                projectile_glyph(landing.x - from.x, landing.y - from.y),
+                0.0,
+            );
+
+            noises.make(landing, NoiseKind::Impact);
+
+            let landing_idx = map.xy_idx(landing.x, landing.y);
+            let victim = if pass_through.contains(&landing) {
+                None
+            } else {
+                map.tile_content[landing_idx]
+                    .iter()
+                    .find(|e| combat_stats.get(**e).is_some())
+                    .copied()
+            };
+            match victim {
+                None => {
+                    log.entries
+                        .push(format!("{}'s shot hits nothing.", shooter_name));
+                }
+                Some(victim) => {
+                    let victim_name = names
+                        .get(victim)
+                        .map_or("something".to_string(), |n| n.name.clone());
+                    let dexterity = attributes.get(shooter).map_or(0, |a| a.dexterity.bonus);
+                    let needed =
+                        BASE_TO_HIT + attributes.get(victim).map_or(0, |a| a.dexterity.bonus);
+                    let natural_roll = rng.roll_dice(1, 20);
+                    let roll = natural_roll + dexterity;
+                    let odds = format!("rolled {}, needed {}", roll, needed);
+                    if natural_roll == 1 || (roll < needed && natural_roll != 20) {
+                        log.entries.push(format!(
+                            "{}'s shot misses {} ({}).",
+                            shooter_name, victim_name, odds
+                        ));
+                    } else {
+                        if shooter == *player_entity
+                            && provoke(&mut allegiances, victim, *player_entity)
+                        {
+                            log.entries.push(format!("{} turns on you!", victim_name));
+                        }
+                        let defense = combat_stats.get(victim).map_or(0, |s| s.defense);
+                        let damage = i32::max(1, weapon.power + dexterity - defense);
+                        log.entries.push(format!(
+                            "{}'s shot hits {}, for {} hp ({}).",
+                            shooter_name, victim_name, damage, odds
+                        ));
+                        SufferDamage::new_damage(
+                            &mut inflict_damage,
+                            victim,
+                            damage,
+                            weapon.damage_type,
+                        );
+                    }
+                }
+            }
+
+            // Thrown weapons survive, but arrows and bolts sometimes snap
+            let kind = weapon.ammo;
+            if kind != AmmoKind::Thrown && rng.roll_dice(1, 4) == 1 {
+                if shooter == *player_entity {
+                    log.entries.push("The shot breaks on impact.".to_string());
+                }
+            } else {
+                let (x, y) = (landing.x, landing.y);
+                lazy.exec_mut(move |ecs| {
+                    spawner::ammo(ecs, x, y, kind, 1);
+                });
+            }
+        }
+
+        wants_shoot.clear();
+    }
+}
@@ -60,7 +60,8 @@ pub fn save_game(ecs: &mut World) {
             Ranged,
             InflictsDamage,
             AreaOfEffect,
-            Confusion,
+            StatusEffects,
+            InflictsStatus,
             ProvidesHealing,
             InBackpack,
             WantsToPickupItem,
@@ -73,10 +74,38 @@ pub fn save_game(ecs: &mut World) {
             DefenseBonus,
             WantsToRemoveItem,
             WeaponStats,
+            Wieldable,
+            Broken,
+            ProvidesRepair,
+            Attributes,
+            Stamina,
+            Exhausted,
+            PowerAttack,
+            Dodging,
+            Knockback,
+            Resistant,
+            Vulnerable,
+            NaturalAttack,
             ParticleLifetime,
             EntryTrigger,
             EntityMoved,
-            SingleActivation
+            SingleActivation,
+            Hidden,
+            Ammo,
+            RangedWeapon,
+            WantsToShoot,
+            LootTable,
+            Corpse,
+            Brain,
+            PackMember,
+            Allegiance,
+            Boss,
+            Telegraph,
+            WindUpAttack,
+            PendingAttack,
+            SoundsAlarm,
+            Teleports,
+            Alerted
         );
     }
 
@@ -144,7 +173,8 @@ pub fn load_game(ecs: &mut World) {
             Ranged,
             InflictsDamage,
             AreaOfEffect,
-            Confusion,
+            StatusEffects,
+            InflictsStatus,
             ProvidesHealing,
             InBackpack,
             WantsToPickupItem,
@@ -157,10 +187,38 @@ pub fn load_game(ecs: &mut World) {
             DefenseBonus,
             WantsToRemoveItem,
             WeaponStats,
+            Wieldable,
+            Broken,
+            ProvidesRepair,
+            Attributes,
+            Stamina,
+            Exhausted,
+            PowerAttack,
+            Dodging,
+            Knockback,
+            Resistant,
+            Vulnerable,
+            NaturalAttack,
             ParticleLifetime,
             EntryTrigger,
             EntityMoved,
-            SingleActivation
+            SingleActivation,
+            Hidden,
+            Ammo,
+            RangedWeapon,
+            WantsToShoot,
+            LootTable,
+            Corpse,
+            Brain,
+            PackMember,
+            Allegiance,
+            Boss,
+            Telegraph,
+            WindUpAttack,
+            PendingAttack,
+            SoundsAlarm,
+            Teleports,
+            Alerted
         );
     }
 
@@ -1,11 +1,14 @@
 use super::{
-    map::find_empty_adjacent, map::MAPWIDTH, random_table::RandomTable, AreaOfEffect, Attribute,
-    Attributes, BlocksTile, CombatStats, Confusion, Consumable, DefenseBonus, EntryTrigger,
-    EquipmentSlot, Equippable, InflictsDamage, Item, MeleePowerBonus, Monster, Name, Player,
-    Position, ProvidesHealing, Ranged, Rect, Renderable, SerializeMe, SingleActivation, Viewshed,
-    WeaponStats,
+    map::find_empty_adjacent, map::Map, map::MAPWIDTH, random_table::RandomTable, Alerted,
+    Allegiance, Ammo, AmmoKind, AreaOfEffect, Attribute, Attributes, Behaviour, BlocksTile, Boss,
+    Brain, CombatStats, Consumable, Corpse, DamageType, DefenseBonus, EntryTrigger, EquipmentSlot,
+    Equippable, Equipped, Faction, Hidden, InBackpack, InflictsDamage, InflictsStatus, Item,
+    LootTable, MeleePowerBonus, Monster, Name, NaturalAttack, PackMember, Player, Position,
+    ProvidesHealing, ProvidesRepair, Ranged, RangedWeapon, Rect, Renderable, Resistant,
+    SerializeMe, SingleActivation, SoundsAlarm, Stamina, StatusKind, Teleports, Viewshed,
+    Vulnerable, WeaponStats, Wieldable, WindUpAttack, WindUpKind, Wits,
 };
-use rltk::{Point, RandomNumberGenerator, RGB};
+use rltk::{RandomNumberGenerator, RGB};
 use specs::prelude::*;
 use specs::saveload::{MarkedBuilder, SimpleMarker};
 use std::collections::HashMap;
@@ -37,52 +40,51 @@ pub fn player(ecs: &mut World, player_x: i32, player_y: i32) -> Entity {
             dexterity: Attribute { base: 10, modifiers: 0, bonus: 0 },
             endurance: Attribute { base: 10, modifiers: 0, bonus: 0 },
         })
+        .with(Stamina { recovery: 0 })
+        .with(Allegiance::new(Faction::Player))
         .marked::<SimpleMarker<SerializeMe>>()
         .build();
 
-    //    let (x, y) = find_empty_adjacent(ecs, player_x, player_y);
-    //    weapon_entity(ecs, x, y, entity);
+    // The weapon entity itself is spawned by the weapon system once this is equipped
+    worn_sword(ecs, entity);
 
     entity
 }
 
-/// Spawns the weapon and returns the entity object.
-pub fn weapon_entity(ecs: &mut World, x: i32, y: i32, owner: Entity) -> Entity {
+/// Spawns the weapon entity for an equipped item and returns the entity object.
+pub fn weapon_entity(ecs: &mut World, x: i32, y: i32, owner: Entity, item: Entity) -> Entity {
+    let wieldable = ecs.read_storage::<Wieldable>().get(item).unwrap().clone();
+    let name = ecs.read_storage::<Name>().get(item).unwrap().name.clone();
+
     ecs.create_entity()
-        .with(Position { x: x, y: y })
+        .with(Position { x, y })
         .with(Renderable {
-            glyph: rltk::to_cp437('*'),
-            fg: RGB::named(rltk::YELLOW),
+            glyph: wieldable.glyph,
+            fg: wieldable.fg,
             bg: RGB::named(rltk::BLACK),
             render_order: 0,
         })
-        .with(WeaponStats { power: 8, owner: owner })
+        .with(WeaponStats {
+            power: wieldable.power,
+            reach: wieldable.reach,
+            damage_type: wieldable.damage_type,
+            owner,
+            item,
+        })
         .with(CombatStats {
-            max_hp: 30,
-            hp: 30,
+            max_hp: wieldable.max_durability,
+            hp: wieldable.durability,
             stamina: 1,
             max_stamina: 1,
             defense: 20,
-            power: 8,
+            power: wieldable.power,
         })
-        .with(Name { name: "Player Weapon".to_string() })
+        .with(Name { name })
         .with(BlocksTile {})
         .marked::<SimpleMarker<SerializeMe>>()
         .build()
 }
 
-pub fn player_weapon(ecs: &mut World) -> Entity {
-    let player_entity = *ecs.fetch::<Entity>();
-    let new_xy;
-    {
-        let player_position = ecs.read_resource::<Point>();
-
-        new_xy = find_empty_adjacent(ecs, player_position.x, player_position.y);
-    }
-    // weapon_entity(ecs, player_position.x, player_position.y, *player_entity);
-    weapon_entity(ecs, new_xy.0, new_xy.1, player_entity)
-}
-
 /// Resets the weapon location
 pub fn reset_weapon_locations(ecs: &mut World) {
     let weapon_stats = ecs.read_storage::<WeaponStats>();
@@ -109,103 +111,465 @@ fn room_table(map_depth: i32) -> RandomTable {
         .add("Troglodyte", 10)
         .add("Skeleton", 2 + map_depth)
         .add("Ogre", 1 + map_depth)
+        .add("Skeleton Archer", map_depth)
+        .add("Troglodyte Shaman", map_depth)
+        .add("Troglodyte Band", map_depth)
+        .add("Skeleton Squad", map_depth - 1)
         .add("Health Potion", 7)
+        .add("Whetstone", 3)
+        .add("Haste Potion", 2)
+        .add("Regeneration Potion", 2)
         // .add("Fireball Scroll", 2 + map_depth)
         // .add("Confusion Scroll", 2 + map_depth)
         // .add("Magic Missile Scroll", 4)
-        // .add("Dagger", 3 * 2)
+        .add("Dagger", 3 * 2)
         .add("Shield", 3)
-        // .add("Longsword", map_depth * 2)
-        // .add("Halberd", map_depth)
+        .add("Longsword", map_depth * 2)
+        .add("Mace", 1 + map_depth)
+        .add("Halberd", map_depth)
+        .add("Bow", 2)
+        .add("Crossbow", map_depth)
+        .add("Arrows", 4)
+        .add("Bolts", 1 + map_depth)
+        .add("Throwing Knives", 3)
         .add("Tower Shield", map_depth)
         .add("Bear Trap", 5)
+        .add("Alarm Trap", 2 + map_depth)
+        .add("Teleport Trap", map_depth)
 }
 
 /// Fills a room with stuff!
 #[allow(clippy::map_entry)]
-pub fn spawn_room(ecs: &mut World, room: &Rect, map_depth: i32) {
+pub fn spawn_room(
+    rng: &mut RandomNumberGenerator,
+    room: &Rect,
+    map_depth: i32,
+    spawn_list: &mut Vec<(usize, String)>,
+) {
     let spawn_table = room_table(map_depth);
     let mut spawn_points: HashMap<usize, String> = HashMap::new();
+    let num_spawns = rng.roll_dice(2, MAX_MONSTERS) + (map_depth - 2);
 
-    // Scope to keep the borrow checker happy
-    {
-        let mut rng = ecs.write_resource::<RandomNumberGenerator>();
-        let num_spawns = rng.roll_dice(2, MAX_MONSTERS) + (map_depth - 2);
-
-        for _i in 0..num_spawns {
-            let mut added = false;
-            let mut tries = 0;
-            while !added && tries < 20 {
-                let x = (room.x1 + rng.roll_dice(1, i32::abs(room.x2 - room.x1))) as usize;
-                let y = (room.y1 + rng.roll_dice(1, i32::abs(room.y2 - room.y1))) as usize;
-                let idx = (y * MAPWIDTH) + x;
-                if !spawn_points.contains_key(&idx) {
-                    spawn_points.insert(idx, spawn_table.roll(&mut rng));
-                    added = true;
-                } else {
-                    tries += 1;
-                }
+    for _i in 0..num_spawns {
+        let mut added = false;
+        let mut tries = 0;
+        while !added && tries < 20 {
+            let x = (room.x1 + rng.roll_dice(1, i32::abs(room.x2 - room.x1))) as usize;
+            let y = (room.y1 + rng.roll_dice(1, i32::abs(room.y2 - room.y1))) as usize;
+            let idx = (y * MAPWIDTH) + x;
+            if !spawn_points.contains_key(&idx) {
+                spawn_points.insert(idx, spawn_table.roll(rng));
+                added = true;
+            } else {
+                tries += 1;
             }
         }
     }
 
-    // Actually spawn the monsters
-    for spawn in spawn_points.iter() {
-        let x = (*spawn.0 % MAPWIDTH) as i32;
-        let y = (*spawn.0 / MAPWIDTH) as i32;
-        spawn_item(ecs, x, y, spawn.1);
+    spawn_list.extend(spawn_points);
+}
+
+/// What each kind of monster might be carrying when it dies.
+fn loot_table(table: &str) -> RandomTable {
+    match table {
+        "Troglodyte" => RandomTable::new()
+            .add("None", 6)
+            .add("Throwing Knives", 2)
+            .add("Health Potion", 2),
+        "Skeleton" => RandomTable::new()
+            .add("None", 4)
+            .add("Dagger", 2)
+            .add("Arrows", 2)
+            .add("Shield", 1)
+            .add("Bow", 1),
+        "Troglodyte Chief" => RandomTable::new()
+            .add("Throwing Knives", 3)
+            .add("Crossbow", 1)
+            .add("Health Potion", 2),
+        "Skeleton Captain" => RandomTable::new()
+            .add("Longsword", 2)
+            .add("Shield", 2)
+            .add("Bolts", 1),
+        "Skeleton Archer" => RandomTable::new()
+            .add("None", 4)
+            .add("Arrows", 2)
+            .add("Dagger", 1),
+        "Troglodyte Shaman" => RandomTable::new()
+            .add("None", 2)
+            .add("Health Potion", 2)
+            .add("Confusion Scroll", 1),
+        "Ogre" => RandomTable::new()
+            .add("None", 2)
+            .add("Mace", 3)
+            .add("Health Potion", 2)
+            .add("Regeneration Potion", 1)
+            .add("Tower Shield", 1),
+        _ => RandomTable::new(),
     }
 }
 
-fn spawn_item(ecs: &mut World, x: i32, y: i32, item: &str) {
+/// Rolls on a loot table, and drops whatever comes up.
+pub fn drop_loot(ecs: &mut World, x: i32, y: i32, table: &str) {
+    let roll = {
+        let mut rng = ecs.write_resource::<RandomNumberGenerator>();
+        loot_table(table).roll(&mut rng)
+    };
+    spawn_item(ecs, x, y, &roll);
+}
+
+/// How many turns a corpse lasts before it has rotted away completely.
+pub const CORPSE_DECAY_TURNS: i32 = 60;
+
+/// Leaves the remains of a creature where it fell.
+pub fn corpse(ecs: &mut World, x: i32, y: i32, of: &str, fg: RGB) {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('%'),
+            fg,
+            bg: RGB::named(rltk::BLACK),
+            render_order: 3,
+        })
+        .with(Name { name: format!("{} corpse", of) })
+        .with(Corpse { of: of.to_string(), turns: CORPSE_DECAY_TURNS })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+pub fn spawn_item(ecs: &mut World, x: i32, y: i32, item: &str) {
     match item.as_ref() {
-        "Troglodyte" => troglodyte(ecs, x, y),
-        "Skeleton" => skeleton(ecs, x, y),
+        "Troglodyte" => {
+            troglodyte(ecs, x, y);
+        }
+        "Skeleton" => {
+            skeleton(ecs, x, y);
+        }
+        "Troglodyte Band" => {
+            let followers = 2 + ecs
+                .write_resource::<RandomNumberGenerator>()
+                .roll_dice(1, 2);
+            pack(ecs, x, y, troglodyte_chief, troglodyte, followers);
+        }
+        "Skeleton Squad" => pack(ecs, x, y, skeleton_captain, skeleton, 3),
+        "Skeleton Archer" => skeleton_archer(ecs, x, y),
+        "Troglodyte Shaman" => troglodyte_shaman(ecs, x, y),
         "Ogre" => ogre(ecs, x, y),
+        "Koschei the Deathless" => boss(ecs, x, y),
         "Health Potion" => health_potion(ecs, x, y),
+        "Whetstone" => whetstone(ecs, x, y),
+        "Haste Potion" => haste_potion(ecs, x, y),
+        "Regeneration Potion" => regeneration_potion(ecs, x, y),
         "Fireball Scroll" => fireball_scroll(ecs, x, y),
-        "Confusion Scroll" => confusion_scroll(ecs, x, y),
+        "Confusion Scroll" => {
+            confusion_scroll(ecs, x, y);
+        }
+        "Frost Bolt Scroll" => {
+            frost_bolt_scroll(ecs, x, y);
+        }
         "Magic Missile Scroll" => magic_missile_scroll(ecs, x, y),
         "Dagger" => dagger(ecs, x, y),
         "Shield" => shield(ecs, x, y),
         "Longsword" => longsword(ecs, x, y),
+        "Mace" => mace(ecs, x, y),
         "Halberd" => halberd(ecs, x, y),
+        "Bow" => {
+            bow(ecs, x, y);
+        }
+        "Crossbow" => crossbow(ecs, x, y),
+        "Arrows" => {
+            ammo(ecs, x, y, AmmoKind::Arrow, 12);
+        }
+        "Bolts" => {
+            ammo(ecs, x, y, AmmoKind::Bolt, 8);
+        }
+        "Throwing Knives" => {
+            ammo(ecs, x, y, AmmoKind::Thrown, 3);
+        }
         "Tower Shield" => tower_shield(ecs, x, y),
         "Bear Trap" => bear_trap(ecs, x, y),
+        "Alarm Trap" => alarm_trap(ecs, x, y),
+        "Teleport Trap" => teleport_trap(ecs, x, y),
         _ => {}
     }
 }
 
-pub fn spawn_locations(ecs: &mut World, positions: &[Position], map_depth: i32) {
+/// Rolls something to put at each of the positions.
+pub fn spawn_locations(
+    rng: &mut RandomNumberGenerator,
+    positions: &[Position],
+    map_depth: i32,
+    spawn_list: &mut Vec<(usize, String)>,
+) {
     let spawn_table = room_table(map_depth);
-    let mut spawn_points: HashMap<usize, String> = HashMap::new();
+    for position in positions {
+        let idx = (position.y as usize * MAPWIDTH) + position.x as usize;
+        spawn_list.push((idx, spawn_table.roll(rng)));
+    }
+}
 
-    {
-        let mut rng = ecs.write_resource::<RandomNumberGenerator>();
+fn skeleton(ecs: &mut World, x: i32, y: i32) -> Entity {
+    monster(
+        ecs,
+        x,
+        y,
+        rltk::to_cp437('S'),
+        "Skeleton",
+        RGB::named(rltk::WHITE),
+        CombatStats::new(8, 5, 2),
+    )
+    .with(Attributes::new(10, 10, 12))
+    .with(LootTable { table: "Skeleton".to_string() })
+    .with(Allegiance::new(Faction::Undead))
+    .with(Brain::new(Behaviour::Idle, Wits::Mindless, 20, 0, 10))
+    .with(NaturalAttack { damage_type: DamageType::Slashing })
+    .with(InflictsStatus { kind: StatusKind::Bleeding, turns: 3, potency: 1, chance: 20 })
+    .with(Resistant { damage_types: vec![DamageType::Piercing, DamageType::Poison] })
+    .with(Vulnerable { damage_types: vec![DamageType::Blunt, DamageType::Fire] })
+    .marked::<SimpleMarker<SerializeMe>>()
+    .build()
+}
+fn troglodyte(ecs: &mut World, x: i32, y: i32) -> Entity {
+    monster(
+        ecs,
+        x,
+        y,
+        rltk::to_cp437('T'),
+        "Troglodyte",
+        RGB::named(rltk::BROWN1),
+        CombatStats::new(6, 4, 1),
+    )
+    .with(Attributes::new(8, 12, 10))
+    .with(LootTable { table: "Troglodyte".to_string() })
+    .with(Allegiance::new(Faction::Troglodytes))
+    .with(Brain::new(Behaviour::Wander, Wits::Cunning, 10, 30, 30))
+    .with(NaturalAttack { damage_type: DamageType::Piercing })
+    .with(InflictsStatus { kind: StatusKind::Poison, turns: 4, potency: 1, chance: 25 })
+    .with(Resistant { damage_types: vec![DamageType::Poison] })
+    .with(Vulnerable { damage_types: vec![DamageType::Cold] })
+    .with(WindUpAttack { kind: WindUpKind::Charge, chance: 25 })
+    .marked::<SimpleMarker<SerializeMe>>()
+    .build()
+}
+fn ogre(ecs: &mut World, x: i32, y: i32) {
+    monster(
+        ecs,
+        x,
+        y,
+        rltk::to_cp437('O'),
+        "Ogre",
+        RGB::named(rltk::GREEN),
+        CombatStats::new(12, 6, 3),
+    )
+    .with(Attributes::new(16, 6, 14))
+    .with(LootTable { table: "Ogre".to_string() })
+    .with(Allegiance::new(Faction::Ogres))
+    .with(Brain::new(Behaviour::Asleep, Wits::Wary, 5, 15, 5))
+    .with(NaturalAttack { damage_type: DamageType::Blunt })
+    .with(InflictsStatus { kind: StatusKind::Stun, turns: 2, potency: 0, chance: 15 })
+    .with(Resistant { damage_types: vec![DamageType::Blunt] })
+    .with(Vulnerable { damage_types: vec![DamageType::Fire] })
+    .with(WindUpAttack { kind: WindUpKind::Smash, chance: 40 })
+    .marked::<SimpleMarker<SerializeMe>>()
+    .build();
+}
 
-        for position in positions {
-            let idx = (position.y as usize * MAPWIDTH) + position.x as usize;
-            spawn_points.insert(idx, spawn_table.roll(&mut rng));
-        }
-    }
+fn skeleton_captain(ecs: &mut World, x: i32, y: i32) -> Entity {
+    monster(
+        ecs,
+        x,
+        y,
+        rltk::to_cp437('S'),
+        "Skeleton Captain",
+        RGB::named(rltk::GOLD),
+        CombatStats::new(12, 6, 3),
+    )
+    .with(Attributes::new(12, 10, 12))
+    .with(LootTable { table: "Skeleton Captain".to_string() })
+    .with(Allegiance::new(Faction::Undead))
+    .with(Brain::new(Behaviour::Idle, Wits::Wary, 20, 0, 10))
+    .with(NaturalAttack { damage_type: DamageType::Slashing })
+    .with(InflictsStatus { kind: StatusKind::Bleeding, turns: 3, potency: 2, chance: 25 })
+    .with(Resistant { damage_types: vec![DamageType::Piercing, DamageType::Poison] })
+    .with(Vulnerable { damage_types: vec![DamageType::Blunt, DamageType::Fire] })
+    .marked::<SimpleMarker<SerializeMe>>()
+    .build()
+}
+fn troglodyte_chief(ecs: &mut World, x: i32, y: i32) -> Entity {
+    monster(
+        ecs,
+        x,
+        y,
+        rltk::to_cp437('T'),
+        "Troglodyte Chief",
+        RGB::named(rltk::ORANGE),
+        CombatStats::new(10, 5, 2),
+    )
+    .with(Attributes::new(12, 12, 10))
+    .with(LootTable { table: "Troglodyte Chief".to_string() })
+    .with(Allegiance::new(Faction::Troglodytes))
+    .with(Brain::new(Behaviour::Wander, Wits::Cunning, 15, 20, 20))
+    .with(NaturalAttack { damage_type: DamageType::Piercing })
+    .with(InflictsStatus { kind: StatusKind::Poison, turns: 4, potency: 2, chance: 25 })
+    .with(Resistant { damage_types: vec![DamageType::Poison] })
+    .with(Vulnerable { damage_types: vec![DamageType::Cold] })
+    .marked::<SimpleMarker<SerializeMe>>()
+    .build()
+}
+
+fn skeleton_archer(ecs: &mut World, x: i32, y: i32) {
+    let archer = monster(
+        ecs,
+        x,
+        y,
+        rltk::to_cp437('S'),
+        "Skeleton Archer",
+        RGB::named(rltk::LIGHT_GRAY),
+        CombatStats::new(6, 3, 1),
+    )
+    .with(Attributes::new(8, 14, 10))
+    .with(LootTable { table: "Skeleton Archer".to_string() })
+    .with(Allegiance::new(Faction::Undead))
+    .with(Brain::new(Behaviour::Idle, Wits::Wary, 20, 0, 10))
+    .with(NaturalAttack { damage_type: DamageType::Blunt })
+    .with(Resistant { damage_types: vec![DamageType::Piercing, DamageType::Poison] })
+    .with(Vulnerable { damage_types: vec![DamageType::Blunt, DamageType::Fire] })
+    .marked::<SimpleMarker<SerializeMe>>()
+    .build();
 
-    for spawn in spawn_points.iter() {
-        let x = (*spawn.0 % MAPWIDTH) as i32;
-        let y = (*spawn.0 / MAPWIDTH) as i32;
-        spawn_item(ecs, x, y, spawn.1);
+    let bow = bow(ecs, x, y);
+    carry(ecs, archer, bow, Some(EquipmentSlot::Ranged));
+    let arrows = ammo(ecs, x, y, AmmoKind::Arrow, 8);
+    carry(ecs, archer, arrows, None);
+}
+fn troglodyte_shaman(ecs: &mut World, x: i32, y: i32) {
+    let shaman = monster(
+        ecs,
+        x,
+        y,
+        rltk::to_cp437('T'),
+        "Troglodyte Shaman",
+        RGB::named(rltk::VIOLET),
+        CombatStats::new(6, 2, 1),
+    )
+    .with(Attributes::new(8, 10, 10))
+    .with(LootTable { table: "Troglodyte Shaman".to_string() })
+    .with(Allegiance::new(Faction::Troglodytes))
+    .with(Brain::new(Behaviour::Wander, Wits::Cunning, 10, 40, 20))
+    .with(NaturalAttack { damage_type: DamageType::Piercing })
+    .with(Resistant { damage_types: vec![DamageType::Poison] })
+    .with(Vulnerable { damage_types: vec![DamageType::Cold] })
+    .marked::<SimpleMarker<SerializeMe>>()
+    .build();
+
+    for _ in 0..2 {
+        let scroll = frost_bolt_scroll(ecs, x, y);
+        carry(ecs, shaman, scroll, None);
     }
+    let scroll = confusion_scroll(ecs, x, y);
+    carry(ecs, shaman, scroll, None);
 }
 
-fn skeleton(ecs: &mut World, x: i32, y: i32) {
-    monster(ecs, x, y, rltk::to_cp437('S'), "Skeleton", RGB::named(rltk::WHITE), 8, 5, 2);
+/// Koschei the Deathless, who waits at the bottom of the dungeon.
+fn boss(ecs: &mut World, x: i32, y: i32) {
+    monster(
+        ecs,
+        x,
+        y,
+        rltk::to_cp437('K'),
+        "Koschei the Deathless",
+        RGB::named(rltk::MAGENTA),
+        CombatStats::new(60, 8, 3),
+    )
+    .with(Attributes::new(14, 12, 16))
+    .with(Allegiance::new(Faction::Undead))
+    .with(Boss { phase: 1, attack_cooldown: 2, summon_cooldown: 0 })
+    .with(NaturalAttack { damage_type: DamageType::Slashing })
+    .with(Resistant {
+        damage_types: vec![DamageType::Piercing, DamageType::Poison, DamageType::Cold],
+    })
+    .with(Vulnerable { damage_types: vec![DamageType::Fire] })
+    .marked::<SimpleMarker<SerializeMe>>()
+    .build();
 }
-fn troglodyte(ecs: &mut World, x: i32, y: i32) {
-    monster(ecs, x, y, rltk::to_cp437('T'), "Troglodyte", RGB::named(rltk::BROWN1), 6, 4, 1);
+
+/// How long the dead the boss raises know where the player is.
+const RAISED_ALERT_TURNS: i32 = 20;
+
+/// A skeleton raised by the boss, which already knows where the player is.
+pub fn raised_skeleton(ecs: &mut World, x: i32, y: i32) {
+    let skeleton = skeleton(ecs, x, y);
+    ecs.write_storage::<Alerted>()
+        .insert(skeleton, Alerted { turns: RAISED_ALERT_TURNS })
+        .expect("Unable to insert alert");
 }
-fn ogre(ecs: &mut World, x: i32, y: i32) {
-    monster(ecs, x, y, rltk::to_cp437('O'), "Ogre", RGB::named(rltk::GREEN), 12, 6, 3);
+
+/// Hands a freshly spawned item to a monster, either to wield in a slot or to keep in
+/// its pack.
+fn carry(ecs: &mut World, owner: Entity, item: Entity, slot: Option<EquipmentSlot>) {
+    ecs.write_storage::<Position>().remove(item);
+    match slot {
+        Some(slot) => {
+            ecs.write_storage::<Equipped>()
+                .insert(item, Equipped { owner, slot })
+                .expect("Unable to insert equipped component");
+        }
+        None => {
+            ecs.write_storage::<InBackpack>()
+                .insert(item, InBackpack { owner })
+                .expect("Unable to insert backpack entry");
+        }
+    }
+}
+
+/// Spawns a leader with a pack of followers gathered round it.
+fn pack(
+    ecs: &mut World,
+    x: i32,
+    y: i32,
+    leader: fn(&mut World, i32, i32) -> Entity,
+    follower: fn(&mut World, i32, i32) -> Entity,
+    followers: i32,
+) {
+    let leader = leader(ecs, x, y);
+    let mut members = vec![leader];
+    for (fx, fy) in pack_positions(ecs, x, y, followers) {
+        members.push(follower(ecs, fx, fy));
+    }
+
+    let mut packs = ecs.write_storage::<PackMember>();
+    for member in members {
+        packs
+            .insert(member, PackMember { leader })
+            .expect("Unable to insert pack member");
+    }
+}
+
+/// Free floor around a pack leader for its followers to start on.
+fn pack_positions(ecs: &World, x: i32, y: i32, count: i32) -> Vec<(i32, i32)> {
+    let map = ecs.fetch::<Map>();
+    let positions = ecs.read_storage::<Position>();
+    let mut taken: Vec<(i32, i32)> = positions.join().map(|p| (p.x, p.y)).collect();
+    let mut found: Vec<(i32, i32)> = Vec::new();
+    for radius in 1..=2 {
+        for fy in y - radius..=y + radius {
+            for fx in x - radius..=x + radius {
+                if found.len() as i32 >= count {
+                    return found;
+                }
+                if fx < 1 || fx > map.width - 2 || fy < 1 || fy > map.height - 2 {
+                    continue;
+                }
+                if map.is_walkable(map.xy_idx(fx, fy)) && !taken.contains(&(fx, fy)) {
+                    taken.push((fx, fy));
+                    found.push((fx, fy));
+                }
+            }
+        }
+    }
+    found
 }
 
+/// Starts building a monster with the parts every archetype shares.
 fn monster<S: ToString>(
     ecs: &mut World,
     x: i32,
@@ -213,27 +577,17 @@ fn monster<S: ToString>(
     glyph: rltk::FontCharType,
     name: S,
     fg: RGB,
-    hp: i32,
-    power: i32,
-    defense: i32,
-) {
+    stats: CombatStats,
+) -> EntityBuilder<'_> {
     ecs.create_entity()
         .with(Position { x, y })
-        .with(Renderable { glyph, fg: fg, bg: RGB::named(rltk::BLACK), render_order: 1 })
+        .with(Renderable { glyph, fg, bg: RGB::named(rltk::BLACK), render_order: 1 })
         .with(Viewshed { visible_tiles: Vec::new(), range: 8, dirty: true })
         .with(Monster {})
         .with(Name { name: name.to_string() })
         .with(BlocksTile {})
-        .with(CombatStats {
-            max_hp: hp,
-            hp: hp,
-            stamina: 2,
-            max_stamina: 2,
-            defense: defense,
-            power: power,
-        })
-        .marked::<SimpleMarker<SerializeMe>>()
-        .build();
+        .with(stats)
+        .with(Stamina { recovery: 0 })
 }
 
 fn health_potion(ecs: &mut World, x: i32, y: i32) {
@@ -253,6 +607,62 @@ fn health_potion(ecs: &mut World, x: i32, y: i32) {
         .build();
 }
 
+fn haste_potion(ecs: &mut World, x: i32, y: i32) {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('!'),
+            fg: RGB::named(rltk::YELLOW),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name { name: "Haste Potion".to_string() })
+        .with(Item {})
+        .with(Consumable {})
+        .with(InflictsStatus { kind: StatusKind::Haste, turns: 10, potency: 0, chance: 100 })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+fn regeneration_potion(ecs: &mut World, x: i32, y: i32) {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('!'),
+            fg: RGB::named(rltk::GREEN),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name { name: "Regeneration Potion".to_string() })
+        .with(Item {})
+        .with(Consumable {})
+        .with(InflictsStatus {
+            kind: StatusKind::Regeneration,
+            turns: 10,
+            potency: 1,
+            chance: 100,
+        })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+fn whetstone(ecs: &mut World, x: i32, y: i32) {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('~'),
+            fg: RGB::named(rltk::LIGHT_GRAY),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name { name: "Whetstone".to_string() })
+        .with(Item {})
+        .with(Consumable {})
+        .with(ProvidesRepair { repair_amount: 20 })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
 fn magic_missile_scroll(ecs: &mut World, x: i32, y: i32) {
     ecs.create_entity()
         .with(Position { x, y })
@@ -266,7 +676,7 @@ fn magic_missile_scroll(ecs: &mut World, x: i32, y: i32) {
         .with(Item {})
         .with(Consumable {})
         .with(Ranged { range: 6 })
-        .with(InflictsDamage { damage: 20 })
+        .with(InflictsDamage { damage: 20, damage_type: DamageType::Piercing })
         .marked::<SimpleMarker<SerializeMe>>()
         .build();
 }
@@ -284,13 +694,13 @@ fn fireball_scroll(ecs: &mut World, x: i32, y: i32) {
         .with(Item {})
         .with(Consumable {})
         .with(Ranged { range: 6 })
-        .with(InflictsDamage { damage: 20 })
+        .with(InflictsDamage { damage: 20, damage_type: DamageType::Fire })
         .with(AreaOfEffect { radius: 3 })
         .marked::<SimpleMarker<SerializeMe>>()
         .build();
 }
 
-fn confusion_scroll(ecs: &mut World, x: i32, y: i32) {
+fn confusion_scroll(ecs: &mut World, x: i32, y: i32) -> Entity {
     ecs.create_entity()
         .with(Position { x, y })
         .with(Renderable {
@@ -303,9 +713,32 @@ fn confusion_scroll(ecs: &mut World, x: i32, y: i32) {
         .with(Item {})
         .with(Consumable {})
         .with(Ranged { range: 6 })
-        .with(Confusion { turns: 4 })
+        .with(InflictsStatus {
+            kind: StatusKind::Confusion,
+            turns: 4,
+            potency: 0,
+            chance: 100,
+        })
         .marked::<SimpleMarker<SerializeMe>>()
-        .build();
+        .build()
+}
+
+fn frost_bolt_scroll(ecs: &mut World, x: i32, y: i32) -> Entity {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437(')'),
+            fg: RGB::named(rltk::LIGHT_BLUE),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name { name: "Frost Bolt Scroll".to_string() })
+        .with(Item {})
+        .with(Consumable {})
+        .with(Ranged { range: 6 })
+        .with(InflictsDamage { damage: 6, damage_type: DamageType::Cold })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build()
 }
 
 fn dagger(ecs: &mut World, x: i32, y: i32) {
@@ -321,6 +754,42 @@ fn dagger(ecs: &mut World, x: i32, y: i32) {
         .with(Item {})
         .with(Equippable { slot: EquipmentSlot::Melee })
         .with(MeleePowerBonus { power: 2 })
+        .with(Wieldable {
+            glyph: rltk::to_cp437('-'),
+            fg: RGB::named(rltk::CYAN),
+            power: 6,
+            reach: 1,
+            durability: 20,
+            max_durability: 20,
+            damage_type: DamageType::Piercing,
+        })
+        .with(InflictsStatus { kind: StatusKind::Bleeding, turns: 3, potency: 1, chance: 30 })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+/// The sword the player starts with, already equipped.
+fn worn_sword(ecs: &mut World, owner: Entity) {
+    ecs.create_entity()
+        .with(Renderable {
+            glyph: rltk::to_cp437('/'),
+            fg: RGB::named(rltk::GREY),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name { name: "Worn Sword".to_string() })
+        .with(Item {})
+        .with(Equippable { slot: EquipmentSlot::Melee })
+        .with(Equipped { owner, slot: EquipmentSlot::Melee })
+        .with(Wieldable {
+            glyph: rltk::to_cp437('*'),
+            fg: RGB::named(rltk::YELLOW),
+            power: 8,
+            reach: 1,
+            durability: 30,
+            max_durability: 30,
+            damage_type: DamageType::Slashing,
+        })
         .marked::<SimpleMarker<SerializeMe>>()
         .build();
 }
@@ -338,6 +807,7 @@ fn shield(ecs: &mut World, x: i32, y: i32) {
         .with(Item {})
         .with(Equippable { slot: EquipmentSlot::Shield })
         .with(DefenseBonus { defense: 1 })
+        .with(Resistant { damage_types: vec![DamageType::Piercing] })
         .marked::<SimpleMarker<SerializeMe>>()
         .build();
 }
@@ -355,6 +825,42 @@ fn longsword(ecs: &mut World, x: i32, y: i32) {
         .with(Item {})
         .with(Equippable { slot: EquipmentSlot::Melee })
         .with(MeleePowerBonus { power: 4 })
+        .with(Wieldable {
+            glyph: rltk::to_cp437('|'),
+            fg: RGB::named(rltk::YELLOW),
+            power: 10,
+            reach: 1,
+            durability: 40,
+            max_durability: 40,
+            damage_type: DamageType::Slashing,
+        })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+fn mace(ecs: &mut World, x: i32, y: i32) {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('/'),
+            fg: RGB::named(rltk::GREY),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name { name: "Mace".to_string() })
+        .with(Item {})
+        .with(Equippable { slot: EquipmentSlot::Melee })
+        .with(MeleePowerBonus { power: 3 })
+        .with(Wieldable {
+            glyph: rltk::to_cp437('♣'),
+            fg: RGB::named(rltk::GREY),
+            power: 9,
+            reach: 1,
+            durability: 45,
+            max_durability: 45,
+            damage_type: DamageType::Blunt,
+        })
+        .with(InflictsStatus { kind: StatusKind::Stun, turns: 1, potency: 0, chance: 10 })
         .marked::<SimpleMarker<SerializeMe>>()
         .build();
 }
@@ -372,10 +878,94 @@ fn halberd(ecs: &mut World, x: i32, y: i32) {
         .with(Item {})
         .with(Equippable { slot: EquipmentSlot::Melee })
         .with(MeleePowerBonus { power: 6 })
+        .with(Wieldable {
+            glyph: rltk::to_cp437('¥'),
+            fg: RGB::named(rltk::WHITE),
+            power: 12,
+            reach: 2,
+            durability: 35,
+            max_durability: 35,
+            damage_type: DamageType::Piercing,
+        })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+fn bow(ecs: &mut World, x: i32, y: i32) -> Entity {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('}'),
+            fg: RGB::named(rltk::BROWN1),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name { name: "Bow".to_string() })
+        .with(Item {})
+        .with(Equippable { slot: EquipmentSlot::Ranged })
+        .with(RangedWeapon {
+            range: 8,
+            power: 6,
+            damage_type: DamageType::Piercing,
+            ammo: AmmoKind::Arrow,
+        })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build()
+}
+
+fn crossbow(ecs: &mut World, x: i32, y: i32) {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('}'),
+            fg: RGB::named(rltk::GREY),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name { name: "Crossbow".to_string() })
+        .with(Item {})
+        .with(Equippable { slot: EquipmentSlot::Ranged })
+        .with(RangedWeapon {
+            range: 6,
+            power: 9,
+            damage_type: DamageType::Piercing,
+            ammo: AmmoKind::Bolt,
+        })
         .marked::<SimpleMarker<SerializeMe>>()
         .build();
 }
 
+/// Spawns a bundle of ammunition on the floor. Throwing knives can be thrown straight
+/// from the pack, so they are a weapon as well.
+pub fn ammo(ecs: &mut World, x: i32, y: i32, kind: AmmoKind, count: i32) -> Entity {
+    let (glyph, fg) = match kind {
+        AmmoKind::Arrow => ('|', RGB::named(rltk::BROWN1)),
+        AmmoKind::Bolt => ('|', RGB::named(rltk::GREY)),
+        AmmoKind::Thrown => ('-', RGB::named(rltk::CYAN)),
+    };
+    let mut builder = ecs
+        .create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437(glyph),
+            fg,
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name { name: kind.bundle_name(count) })
+        .with(Item {})
+        .with(Ammo { kind, count });
+    if kind == AmmoKind::Thrown {
+        builder = builder.with(Ranged { range: 5 }).with(RangedWeapon {
+            range: 5,
+            power: 4,
+            damage_type: DamageType::Piercing,
+            ammo: AmmoKind::Thrown,
+        });
+    }
+    builder.marked::<SimpleMarker<SerializeMe>>().build()
+}
+
 fn tower_shield(ecs: &mut World, x: i32, y: i32) {
     ecs.create_entity()
         .with(Position { x, y })
@@ -389,6 +979,9 @@ fn tower_shield(ecs: &mut World, x: i32, y: i32) {
         .with(Item {})
         .with(Equippable { slot: EquipmentSlot::Shield })
         .with(DefenseBonus { defense: 2 })
+        .with(Resistant {
+            damage_types: vec![DamageType::Piercing, DamageType::Slashing],
+        })
         .marked::<SimpleMarker<SerializeMe>>()
         .build();
 }
@@ -405,7 +998,49 @@ fn bear_trap(ecs: &mut World, x: i32, y: i32) {
         .with(Name { name: "Bear Trap".to_string() })
         .with(EntryTrigger {})
         .with(SingleActivation {})
-        .with(InflictsDamage { damage: 6 })
+        .with(InflictsDamage { damage: 6, damage_type: DamageType::Piercing })
+        .with(InflictsStatus {
+            kind: StatusKind::Immobilised,
+            turns: 6,
+            potency: 0,
+            chance: 100,
+        })
+        .with(Hidden {})
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+fn alarm_trap(ecs: &mut World, x: i32, y: i32) {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('^'),
+            fg: RGB::named(rltk::YELLOW),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name { name: "Alarm Trap".to_string() })
+        .with(EntryTrigger {})
+        .with(SingleActivation {})
+        .with(SoundsAlarm {})
+        .with(Hidden {})
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+fn teleport_trap(ecs: &mut World, x: i32, y: i32) {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('^'),
+            fg: RGB::named(rltk::MAGENTA),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name { name: "Teleport Trap".to_string() })
+        .with(EntryTrigger {})
+        .with(Teleports {})
+        .with(Hidden {})
         .marked::<SimpleMarker<SerializeMe>>()
         .build();
 }
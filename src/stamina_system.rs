@@ -0,0 +1,115 @@
+use super::{gamelog::GameLog, Attributes, CombatStats, Exhausted, RunState, Stamina};
+use specs::prelude::*;
+
+/// Recovery points needed to regain one point of stamina.
+const RECOVERY_PER_POINT: i32 = 10;
+
+/// Recovery points every entity gathers per turn, before endurance is taken into account.
+const BASE_RECOVERY: i32 = 2;
+
+/// Everything that can cost stamina.
+#[derive(PartialEq, Copy, Clone)]
+pub enum StaminaAction {
+    WeaponSwing,
+    ShieldMove,
+    Dodge,
+    PowerAttack,
+}
+
+impl StaminaAction {
+    /// The stamina cost table
+    pub fn cost(self) -> i32 {
+        match self {
+            StaminaAction::WeaponSwing => 1,
+            StaminaAction::ShieldMove => 1,
+            StaminaAction::Dodge => 2,
+            StaminaAction::PowerAttack => 2,
+        }
+    }
+
+    /// The action the player is about to take in the given run state, if any
+    pub fn for_runstate(runstate: RunState) -> Option<StaminaAction> {
+        match runstate {
+            RunState::MoveWeapon => Some(StaminaAction::WeaponSwing),
+            RunState::MoveShield => Some(StaminaAction::ShieldMove),
+            RunState::Dodge => Some(StaminaAction::Dodge),
+            _ => None,
+        }
+    }
+}
+
+/// Spends the stamina for an action, returning false if it can't be afforded.
+///
+/// Running out of stamina leaves the entity exhausted until it has recovered.
+pub fn spend_stamina(
+    entity: Entity,
+    stats: &mut CombatStats,
+    exhausted: &mut WriteStorage<Exhausted>,
+    action: StaminaAction,
+) -> bool {
+    let cost = action.cost();
+    if exhausted.get(entity).is_some() || stats.stamina < cost {
+        return false;
+    }
+
+    stats.stamina -= cost;
+    if stats.stamina == 0 {
+        exhausted
+            .insert(entity, Exhausted {})
+            .expect("Unable to insert exhaustion");
+    }
+    true
+}
+
+/// Passively regenerates stamina each turn, faster for those with more endurance.
+pub struct StaminaSystem {}
+
+impl<'a> System<'a> for StaminaSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, Entity>,
+        ReadExpect<'a, RunState>,
+        WriteExpect<'a, GameLog>,
+        WriteStorage<'a, CombatStats>,
+        WriteStorage<'a, Stamina>,
+        WriteStorage<'a, Exhausted>,
+        ReadStorage<'a, Attributes>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            player_entity,
+            runstate,
+            mut gamelog,
+            mut combat_stats,
+            mut staminas,
+            mut exhausted,
+            attributes,
+        ) = data;
+
+        // Once per turn, same as the monsters acting
+        if *runstate != RunState::MonsterTurn {
+            return;
+        }
+
+        for (entity, stats, stamina) in (&entities, &mut combat_stats, &mut staminas).join() {
+            let endurance_bonus = attributes.get(entity).map_or(0, |a| a.endurance.bonus);
+            stamina.recovery += i32::max(1, BASE_RECOVERY + endurance_bonus);
+            while stamina.recovery >= RECOVERY_PER_POINT {
+                stamina.recovery -= RECOVERY_PER_POINT;
+                stats.stamina = i32::min(stats.max_stamina, stats.stamina + 1);
+            }
+
+            if exhausted.get(entity).is_some() && stats.stamina * 2 >= stats.max_stamina {
+                exhausted.remove(entity);
+                if entity == *player_entity {
+                    gamelog
+                        .entries
+                        .push("You get your breath back.".to_string());
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use super::{
+    gamelog::GameLog, CombatStats, DamageType, RunState, StatusEffects, StatusKind, SufferDamage,
+};
+use specs::prelude::*;
+use specs::storage::MaskedStorage;
+use std::ops::Deref;
+
+/// The strength check needed to break out of whatever is holding you in place.
+pub const BREAK_FREE_DC: i32 = 14;
+
+/// Ticks every status effect once per round: damage and healing over time are applied,
+/// and effects that have run their course wear off.
+pub struct StatusEffectSystem {}
+
+impl<'a> System<'a> for StatusEffectSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, Entity>,
+        ReadExpect<'a, RunState>,
+        WriteExpect<'a, GameLog>,
+        WriteStorage<'a, StatusEffects>,
+        WriteStorage<'a, CombatStats>,
+        WriteStorage<'a, SufferDamage>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            player_entity,
+            runstate,
+            mut log,
+            mut statuses,
+            mut combat_stats,
+            mut inflict_damage,
+        ) = data;
+
+        if *runstate != RunState::MonsterTurn {
+            return;
+        }
+
+        let mut cured: Vec<Entity> = Vec::new();
+        for (entity, statuses) in (&entities, &mut statuses).join() {
+            for effect in statuses.effects.iter_mut() {
+                match effect.kind {
+                    StatusKind::Poison => SufferDamage::new_damage(
+                        &mut inflict_damage,
+                        entity,
+                        effect.potency,
+                        DamageType::Poison,
+                    ),
+                    StatusKind::Bleeding => SufferDamage::new_damage(
+                        &mut inflict_damage,
+                        entity,
+                        effect.potency,
+                        DamageType::Slashing,
+                    ),
+                    StatusKind::Regeneration => {
+                        if let Some(stats) = combat_stats.get_mut(entity) {
+                            stats.hp = i32::min(stats.max_hp, stats.hp + effect.potency);
+                        }
+                    }
+                    _ => {}
+                }
+                effect.turns -= 1;
+
+                if effect.turns < 1 && entity == *player_entity {
+                    log.entries
+                        .push(format!("You are no longer {}.", effect.kind.name().to_lowercase()));
+                }
+            }
+
+            statuses.effects.retain(|e| e.turns > 0);
+            if statuses.effects.is_empty() {
+                cured.push(entity);
+            }
+        }
+
+        for entity in cured {
+            statuses.remove(entity);
+        }
+    }
+}
+
+/// Whether an entity is currently suffering from a particular effect.
+pub fn is_affected<D>(
+    statuses: &Storage<StatusEffects, D>,
+    entity: Entity,
+    kind: StatusKind,
+) -> bool
+where
+    D: Deref<Target = MaskedStorage<StatusEffects>>,
+{
+    statuses.get(entity).is_some_and(|s| s.has(kind))
+}
+
+/// A hasted player gets a free action every other turn. The free action still counts
+/// towards the haste wearing off.
+pub fn player_gets_extra_action(ecs: &mut World) -> bool {
+    let player_entity = ecs.fetch::<Entity>();
+    let mut statuses = ecs.write_storage::<StatusEffects>();
+    let haste = statuses
+        .get_mut(*player_entity)
+        .and_then(|s| s.effects.iter_mut().find(|e| e.kind == StatusKind::Haste));
+
+    match haste {
+        Some(haste) if haste.turns % 2 == 0 => {
+            haste.turns -= 1;
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Monsters get two turns for every action of a slowed player.
+pub fn monsters_get_extra_turn(ecs: &World) -> bool {
+    let player_entity = ecs.fetch::<Entity>();
+    let statuses = ecs.read_storage::<StatusEffects>();
+    statuses
+        .get(*player_entity)
+        .and_then(|s| s.get(StatusKind::Slow))
+        .is_some_and(|slow| slow.turns % 2 == 1)
+}
@@ -0,0 +1,124 @@
+use super::{
+    gamelog::GameLog,
+    noise_system::{NoiseKind, Noises},
+    particle_system::ParticleBuilder,
+    CombatStats, Map, Name, PendingAttack, Position, RunState, SufferDamage, Telegraph,
+    WeaponStats,
+};
+use rltk::Point;
+use specs::prelude::*;
+
+/// How long anyone has to get out of the way of an attack once it starts winding up.
+pub const WIND_UP_TURNS: i32 = 1;
+
+/// An attack that shows which tiles it is about to land on.
+pub trait Telegraphed {
+    fn tiles(&self) -> &[Point];
+}
+
+impl Telegraphed for Telegraph {
+    fn tiles(&self) -> &[Point] {
+        &self.tiles
+    }
+}
+
+impl Telegraphed for PendingAttack {
+    fn tiles(&self) -> &[Point] {
+        &self.tiles
+    }
+}
+
+/// Counts down the attacks being wound up, and lands the ones that are ready on whatever
+/// is still standing in their tiles.
+pub struct TelegraphSystem {}
+
+impl<'a> System<'a> for TelegraphSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, Map>,
+        ReadExpect<'a, RunState>,
+        WriteExpect<'a, GameLog>,
+        WriteExpect<'a, ParticleBuilder>,
+        WriteExpect<'a, Noises>,
+        WriteStorage<'a, Telegraph>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, CombatStats>,
+        ReadStorage<'a, WeaponStats>,
+        WriteStorage<'a, SufferDamage>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            map,
+            runstate,
+            mut log,
+            mut particle_builder,
+            mut noises,
+            mut telegraphs,
+            positions,
+            names,
+            combat_stats,
+            weapon_stats,
+            mut inflict_damage,
+        ) = data;
+
+        if *runstate != RunState::MonsterTurn {
+            return;
+        }
+
+        let mut landed: Vec<Entity> = Vec::new();
+        for (attacker, telegraph) in (&entities, &mut telegraphs).join() {
+            telegraph.turns -= 1;
+            if telegraph.turns > 0 {
+                continue;
+            }
+            landed.push(attacker);
+            // Boxed in, an attack can end up with nowhere to land
+            if telegraph.tiles.is_empty() {
+                continue;
+            }
+
+            let centre = positions
+                .get(attacker)
+                .map_or_else(|| telegraph.tiles[0], |pos| Point::new(pos.x, pos.y));
+            particle_builder.blast(
+                centre,
+                &telegraph.tiles,
+                rltk::RGB::named(rltk::ORANGE_RED),
+                0.0,
+            );
+            noises.make(centre, NoiseKind::Blast);
+
+            for tile in telegraph.tiles.iter() {
+                let idx = map.xy_idx(tile.x, tile.y);
+                for victim in map.tile_content[idx].iter() {
+                    // Weapons are too small to be caught up in it
+                    if *victim == attacker
+                        || combat_stats.get(*victim).is_none()
+                        || weapon_stats.get(*victim).is_some()
+                    {
+                        continue;
+                    }
+                    let victim_name = names
+                        .get(*victim)
+                        .map_or("Something".to_string(), |n| n.name.clone());
+                    log.entries
+                        .push(format!("{} is caught by the {}!", victim_name, telegraph.name));
+                    SufferDamage::new_damage(
+                        &mut inflict_damage,
+                        *victim,
+                        telegraph.damage,
+                        telegraph.damage_type,
+                    );
+                }
+            }
+        }
+
+        for attacker in landed {
+            telegraphs.remove(attacker);
+        }
+    }
+}
@@ -1,9 +1,16 @@
 use super::{
-    gamelog::GameLog, EntityMoved, EntryTrigger, InflictsDamage, Map, Name, Position,
-    SingleActivation, SufferDamage,
+    gamelog::GameLog,
+    noise_system::{NoiseKind, Noises},
+    spawner, Alerted, EntityMoved, EntryTrigger, Hidden, InflictsDamage, InflictsStatus, Map,
+    Monster, Name, Position, SingleActivation, SoundsAlarm, StatusEffects, SufferDamage, Teleports,
+    Viewshed,
 };
+use rltk::{Point, RandomNumberGenerator};
 use specs::prelude::*;
 
+/// How long monsters woken by an alarm know where the player is.
+const ALARM_TURNS: i32 = 20;
+
 pub struct TriggerSystem {}
 
 impl<'a> System<'a> for TriggerSystem {
@@ -11,7 +18,7 @@ impl<'a> System<'a> for TriggerSystem {
     type SystemData = (
         ReadExpect<'a, Map>,
         WriteStorage<'a, EntityMoved>,
-        ReadStorage<'a, Position>,
+        WriteStorage<'a, Position>,
         ReadStorage<'a, EntryTrigger>,
         ReadStorage<'a, Name>,
         Entities<'a>,
@@ -19,13 +26,26 @@ impl<'a> System<'a> for TriggerSystem {
         ReadStorage<'a, InflictsDamage>,
         WriteStorage<'a, SufferDamage>,
         ReadStorage<'a, SingleActivation>,
+        ReadStorage<'a, InflictsStatus>,
+        WriteStorage<'a, StatusEffects>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        WriteStorage<'a, Hidden>,
+        ReadStorage<'a, SoundsAlarm>,
+        ReadStorage<'a, Teleports>,
+        ReadStorage<'a, Monster>,
+        WriteStorage<'a, Alerted>,
+        WriteStorage<'a, Viewshed>,
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, Point>,
+        Read<'a, LazyUpdate>,
+        WriteExpect<'a, Noises>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
         let (
             map,
             mut entity_moved,
-            position,
+            mut positions,
             entry_trigger,
             names,
             entities,
@@ -33,11 +53,26 @@ impl<'a> System<'a> for TriggerSystem {
             inflicts_damage,
             mut inflict_damage,
             single_activation,
+            inflicts_status,
+            mut statuses,
+            mut rng,
+            mut hidden,
+            sounds_alarm,
+            teleports,
+            monsters,
+            mut alerted,
+            mut viewsheds,
+            player_entity,
+            mut player_pos,
+            lazy,
+            mut noises,
         ) = data;
 
         let mut remove_entities: Vec<Entity> = Vec::new();
+        let mut alarm = false;
+        let mut teleported: Vec<Entity> = Vec::new();
 
-        for (entity, mut _entity_moved, pos) in (&entities, &mut entity_moved, &position).join() {
+        for (entity, mut _entity_moved, pos) in (&entities, &mut entity_moved, &positions).join() {
             let idx = map.xy_idx(pos.x, pos.y);
             for entity_id in map.tile_content[idx].iter() {
                 if entity != *entity_id {
@@ -49,6 +84,8 @@ impl<'a> System<'a> for TriggerSystem {
                             if let Some(name) = name {
                                 log.entries.push(format!("{} triggers!", &name.name));
                             }
+                            hidden.remove(*entity_id);
+                            noises.make(Point::new(pos.x, pos.y), NoiseKind::Trap);
 
                             let damage = inflicts_damage.get(*entity_id);
                             if let Some(damage) = damage {
@@ -57,9 +94,30 @@ impl<'a> System<'a> for TriggerSystem {
                                     &mut inflict_damage,
                                     entity,
                                     damage.damage,
+                                    damage.damage_type,
                                 );
                             }
 
+                            let status = inflicts_status.get(*entity_id);
+                            if let Some(status) = status {
+                                if rng.roll_dice(1, 100) <= status.chance {
+                                    StatusEffects::add_effect(
+                                        &mut statuses,
+                                        entity,
+                                        status.kind,
+                                        status.turns,
+                                        status.potency,
+                                    );
+                                }
+                            }
+
+                            if sounds_alarm.get(*entity_id).is_some() {
+                                alarm = true;
+                            }
+                            if teleports.get(*entity_id).is_some() {
+                                teleported.push(entity);
+                            }
+
                             let sa = single_activation.get(*entity_id);
                             if let Some(_sa) = sa {
                                 remove_entities.push(*entity_id);
@@ -70,6 +128,45 @@ impl<'a> System<'a> for TriggerSystem {
             }
         }
 
+        if alarm {
+            log.entries
+                .push("A deafening alarm rings out across the level!".to_string());
+            for (monster, _monster) in (&entities, &monsters).join() {
+                alerted
+                    .insert(monster, Alerted { turns: ALARM_TURNS })
+                    .expect("Unable to insert alert");
+            }
+        }
+
+        for entity in teleported {
+            let mut tries = 0;
+            while tries < 1000 {
+                let x = rng.range(1, map.width - 1);
+                let y = rng.range(1, map.height - 1);
+                if map.blocked[map.xy_idx(x, y)] {
+                    tries += 1;
+                    continue;
+                }
+
+                if let Some(pos) = positions.get_mut(entity) {
+                    pos.x = x;
+                    pos.y = y;
+                }
+                if let Some(viewshed) = viewsheds.get_mut(entity) {
+                    viewshed.dirty = true;
+                }
+                if entity == *player_entity {
+                    player_pos.x = x;
+                    player_pos.y = y;
+                    log.entries
+                        .push("The world lurches around you.".to_string());
+                    // Whatever was wielded comes along too
+                    lazy.exec_mut(spawner::reset_weapon_locations);
+                }
+                break;
+            }
+        }
+
         for single_use in remove_entities.iter() {
             entities
                 .delete(*single_use)
@@ -1,20 +1,40 @@
-use super::{Map, Player, Position, Viewshed};
-use rltk::{field_of_view, Point};
+use super::{gamelog::GameLog, Attributes, Hidden, Map, Name, Player, Position, Viewshed};
+use rltk::{field_of_view, Point, RandomNumberGenerator};
 use specs::prelude::*;
 
+/// What the player has to beat to notice a hidden trap just by walking past it.
+pub const PASSIVE_SPOT_DC: i32 = 18;
+
 pub struct VisibilitySystem {}
 
 impl<'a> System<'a> for VisibilitySystem {
+    #[allow(clippy::type_complexity)]
     type SystemData = (
         WriteExpect<'a, Map>,
         Entities<'a>,
         WriteStorage<'a, Viewshed>,
         ReadStorage<'a, Position>,
         ReadStorage<'a, Player>,
+        WriteStorage<'a, Hidden>,
+        ReadStorage<'a, Attributes>,
+        ReadStorage<'a, Name>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        WriteExpect<'a, GameLog>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mut map, entities, mut viewshed, pos, player) = data;
+        let (
+            mut map,
+            entities,
+            mut viewshed,
+            pos,
+            player,
+            mut hidden,
+            attributes,
+            names,
+            mut rng,
+            mut log,
+        ) = data;
 
         for (ent, viewshed, pos) in (&entities, &mut viewshed, &pos).join() {
             if viewshed.dirty {
@@ -35,6 +55,22 @@ impl<'a> System<'a> for VisibilitySystem {
                         let idx = map.xy_idx(vis.x, vis.y);
                         map.revealed_tiles[idx] = true;
                         map.visible_tiles[idx] = true;
+
+                        // Chance to notice anything hidden in view
+                        for e in map.tile_content[idx].iter() {
+                            if hidden.get(*e).is_none() {
+                                continue;
+                            }
+                            let spotted = attributes
+                                .get(ent)
+                                .is_some_and(|a| a.dexterity.check(&mut rng, PASSIVE_SPOT_DC));
+                            if spotted {
+                                if let Some(name) = names.get(*e) {
+                                    log.entries.push(format!("You spot a {}.", &name.name));
+                                }
+                                hidden.remove(*e);
+                            }
+                        }
                     }
                 }
             }
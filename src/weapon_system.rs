@@ -0,0 +1,135 @@
+use super::{
+    gamelog::GameLog, map::find_empty_adjacent, spawner, Broken, CombatStats, EquipmentSlot,
+    Equipped, Name, Position, Renderable, WeaponStats, Wieldable,
+};
+use specs::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+/// Keeps each weapon entity in the shape of the melee item its owner has equipped.
+///
+/// Equipping a new item reshapes the weapon, and unequipping it removes the weapon
+/// entity entirely, leaving the owner unarmed. Weapons worn down to nothing break,
+/// and fall to the floor as a broken item.
+pub struct WeaponSystem {}
+
+impl<'a> System<'a> for WeaponSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, GameLog>,
+        Read<'a, LazyUpdate>,
+        WriteStorage<'a, Equipped>,
+        WriteStorage<'a, Position>,
+        WriteStorage<'a, Broken>,
+        WriteStorage<'a, Wieldable>,
+        WriteStorage<'a, WeaponStats>,
+        WriteStorage<'a, CombatStats>,
+        WriteStorage<'a, Renderable>,
+        WriteStorage<'a, Name>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            player_entity,
+            mut gamelog,
+            lazy,
+            mut equipped,
+            mut positions,
+            mut broken,
+            mut wieldables,
+            mut weapon_stats,
+            mut combat_stats,
+            mut renderables,
+            mut names,
+        ) = data;
+
+        let mut broken_weapons: Vec<(Entity, Entity, Position)> = Vec::new();
+        for (stats, weapon_combat, pos) in (&weapon_stats, &combat_stats, &positions).join() {
+            if weapon_combat.hp < 1 {
+                broken_weapons.push((stats.owner, stats.item, pos.clone()));
+            }
+        }
+        for (owner, item, pos) in broken_weapons {
+            equipped.remove(item);
+            positions
+                .insert(item, pos)
+                .expect("Unable to insert position");
+            broken
+                .insert(item, Broken {})
+                .expect("Unable to insert broken marker");
+            if let Some(name) = names.get_mut(item) {
+                if owner == *player_entity {
+                    gamelog.entries.push(format!("Your {} breaks!", name.name));
+                }
+                name.name = format!("Broken {}", name.name);
+            }
+        }
+
+        // Work out what everyone is currently wielding
+        let mut wielded: HashMap<Entity, Entity> = HashMap::new();
+        for (item, equipped_by, _wieldable) in (&entities, &equipped, &wieldables).join() {
+            if equipped_by.slot == EquipmentSlot::Melee {
+                wielded.insert(equipped_by.owner, item);
+            }
+        }
+
+        let mut armed: HashSet<Entity> = HashSet::new();
+        for (weapon, stats, weapon_combat, render) in
+            (&entities, &mut weapon_stats, &mut combat_stats, &mut renderables).join()
+        {
+            // The item remembers how worn its weapon is, so it survives being swapped out
+            if let Some(wieldable) = wieldables.get_mut(stats.item) {
+                wieldable.durability = i32::max(0, weapon_combat.hp);
+            }
+
+            match wielded.get(&stats.owner) {
+                None => {
+                    entities.delete(weapon).expect("Unable to delete weapon");
+                    if stats.owner == *player_entity {
+                        gamelog
+                            .entries
+                            .push("You are fighting unarmed.".to_string());
+                    }
+                }
+                Some(item) => {
+                    armed.insert(stats.owner);
+                    if *item == stats.item {
+                        continue;
+                    }
+
+                    let wieldable = wieldables.get(*item).unwrap();
+                    stats.item = *item;
+                    stats.power = wieldable.power;
+                    stats.reach = wieldable.reach;
+                    stats.damage_type = wieldable.damage_type;
+                    weapon_combat.power = wieldable.power;
+                    weapon_combat.hp = wieldable.durability;
+                    weapon_combat.max_hp = wieldable.max_durability;
+                    render.glyph = wieldable.glyph;
+                    render.fg = wieldable.fg;
+
+                    let item_name = names.get(*item).unwrap().name.clone();
+                    if let Some(weapon_name) = names.get_mut(weapon) {
+                        weapon_name.name = item_name;
+                    }
+                }
+            }
+        }
+
+        // Anyone wielding something without a weapon entity gets one beside them
+        for (owner, item) in wielded.iter() {
+            if armed.contains(owner) {
+                continue;
+            }
+            if let Some(pos) = positions.get(*owner) {
+                let (owner, item, x, y) = (*owner, *item, pos.x, pos.y);
+                lazy.exec_mut(move |ecs| {
+                    let (x, y) = find_empty_adjacent(ecs, x, y);
+                    spawner::weapon_entity(ecs, x, y, owner, item);
+                });
+            }
+        }
+    }
+}